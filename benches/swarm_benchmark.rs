@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use wingbeat::{Subgraph, TornadoSwarm, Vec3, PromptProcessor};
+use wingbeat::{Subgraph, TornadoSwarm, Vec3, PromptProcessor, EnhancedProcessor, InferenceEngine, WingbeatRng};
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
@@ -57,7 +57,7 @@ fn benchmark_prompt_processing(c: &mut Criterion) {
                 let swarm = Arc::new(TornadoSwarm::new());
                 let processor = PromptProcessor::new(swarm);
                 
-                let prompt_id = processor.send_prompt("Test prompt for benchmarking").await;
+                let prompt_id = processor.send_prompt("Test prompt for benchmarking").await.unwrap();
                 
                 // Process a few steps
                 for _ in 0..5 {
@@ -71,11 +71,42 @@ fn benchmark_prompt_processing(c: &mut Criterion) {
     });
 }
 
+fn benchmark_distributed_inference(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut rng = WingbeatRng::seed_from_u64(0);
+    
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(2, 64, 1000, &mut rng);
+    
+    // Eight tornadoes on a ring so every layer lands on a different one
+    let swarm = Arc::new(TornadoSwarm::new());
+    rt.block_on(async {
+        for i in 0..8 {
+            let angle = i as f32 * std::f32::consts::TAU / 8.0;
+            swarm.spawn_tornado_seeded(Vec3::new(angle.cos() * 50.0, angle.sin() * 50.0, 0.0), &mut rng).await;
+        }
+    });
+    let mut processor = EnhancedProcessor::new(swarm);
+    
+    c.bench_function("distributed inference", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let output = processor
+                    .infer_distributed(&engine, black_box("the swarm carries each layer in turn"))
+                    .await
+                    .unwrap();
+                black_box(output);
+            });
+        });
+    });
+}
+
 criterion_group!(
     benches,
     benchmark_subgraph_creation,
     benchmark_subgraph_splitting,
     benchmark_tornado_operations,
-    benchmark_prompt_processing
+    benchmark_prompt_processing,
+    benchmark_distributed_inference
 );
 criterion_main!(benches); 
\ No newline at end of file
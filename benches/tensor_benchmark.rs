@@ -0,0 +1,124 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use wingbeat::{Tensor, TensorOps, WingbeatRng, LayerOperation, LayerContext, ModelWeights};
+use wingbeat::inference::layer_ops::{PoolKind, PoolingLayer};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+fn benchmark_matmul_paths(c: &mut Criterion) {
+    let mut rng = WingbeatRng::seed_from_u64(0);
+    let input = Tensor::random_seeded(vec![128, 512], &mut rng);
+    let weight = Tensor::random_seeded(vec![512, 512], &mut rng);
+    
+    let mut group = c.benchmark_group("matmul [128,512]x[512,512]");
+    group.sample_size(10);
+    
+    group.bench_function("naive", |b| {
+        b.iter(|| black_box(black_box(&input).matmul_naive(black_box(&weight)).unwrap()));
+    });
+    
+    group.bench_function("transposed", |b| {
+        b.iter(|| black_box(black_box(&input).matmul_transposed(black_box(&weight)).unwrap()));
+    });
+    
+    group.finish();
+}
+
+fn benchmark_transformer_scale(c: &mut Criterion) {
+    let mut rng = WingbeatRng::seed_from_u64(0);
+    let activations = Tensor::random_seeded(vec![128, 768], &mut rng);
+    let projection = Tensor::random_seeded(vec![768, 768], &mut rng);
+    let up_projection = Tensor::random_seeded(vec![768, 3072], &mut rng);
+    let scores = Tensor::random_seeded(vec![128, 128], &mut rng);
+    
+    let mut group = c.benchmark_group("transformer scale (hidden 768)");
+    group.sample_size(10);
+    
+    group.bench_function("matmul [128,768]x[768,768]", |b| {
+        b.iter(|| black_box(black_box(&activations).matmul(black_box(&projection)).unwrap()));
+    });
+    
+    group.bench_function("matmul [768,768]x[768,3072]", |b| {
+        b.iter(|| black_box(black_box(&projection).matmul(black_box(&up_projection)).unwrap()));
+    });
+    
+    group.bench_function("softmax [128,128]", |b| {
+        b.iter(|| black_box(black_box(&scores).softmax()));
+    });
+    
+    group.bench_function("transpose [768,3072]", |b| {
+        b.iter(|| black_box(black_box(&up_projection).transpose()));
+    });
+    
+    group.finish();
+}
+
+fn benchmark_elementwise(c: &mut Criterion) {
+    // Run with `--features simd` to compare the vectorized kernels against the scalar loop
+    let mut rng = WingbeatRng::seed_from_u64(0);
+    let activations = Tensor::random_seeded(vec![768, 3072], &mut rng);
+    let other = Tensor::random_seeded(vec![768, 3072], &mut rng);
+    
+    let mut group = c.benchmark_group("elementwise [768,3072]");
+    group.sample_size(10);
+    
+    group.bench_function("relu (scalar reference)", |b| {
+        b.iter(|| black_box(black_box(&activations).data.iter().map(|&x| x.max(0.0)).collect::<Vec<f32>>()));
+    });
+    
+    group.bench_function("relu", |b| {
+        b.iter(|| black_box(black_box(&activations).relu()));
+    });
+    
+    group.bench_function("relu_ (in place)", |b| {
+        let mut buffer = activations.clone();
+        b.iter(|| black_box(&mut buffer).relu_());
+    });
+    
+    group.bench_function("add", |b| {
+        b.iter(|| black_box(black_box(&activations).add(black_box(&other)).unwrap()));
+    });
+    
+    group.bench_function("multiply", |b| {
+        b.iter(|| black_box(black_box(&activations).multiply(black_box(&other)).unwrap()));
+    });
+    
+    group.finish();
+}
+
+fn benchmark_layer_input_passing(c: &mut Criterion) {
+    // Pooling is cheap, so the cost of cloning the input into a LayerContext shows up clearly
+    let mut rng = WingbeatRng::seed_from_u64(0);
+    let hidden = Tensor::random_seeded(vec![512, 768], &mut rng);
+    let layer = PoolingLayer::new(Uuid::nil(), PoolKind::Mean);
+    let weights = ModelWeights::new();
+    
+    let mut group = c.benchmark_group("mean pooling [512,768]");
+    group.sample_size(10);
+    
+    group.bench_function("execute (cloned context)", |b| {
+        b.iter(|| {
+            let context = LayerContext {
+                input: black_box(&hidden).clone(),
+                output: None,
+                attention_mask: None,
+                metadata: HashMap::new(),
+            };
+            black_box(layer.execute(context, &weights).unwrap())
+        });
+    });
+    
+    group.bench_function("forward (borrowed)", |b| {
+        b.iter(|| black_box(layer.forward(black_box(&hidden), &weights).unwrap()));
+    });
+    
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_matmul_paths,
+    benchmark_transformer_scale,
+    benchmark_elementwise,
+    benchmark_layer_input_passing
+);
+criterion_main!(benches);
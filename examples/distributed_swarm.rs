@@ -7,6 +7,9 @@ use tokio::sync::RwLock;
 /// Example demonstrating distributed swarm behavior
 #[tokio::main]
 async fn main() {
+    // Honour NO_COLOR / WINGBEAT_OUTPUT and report swarm activity through tracing
+    wingbeat::output::init_from_env();
+    
     println!("{}", "═══════════════════════════════════════════".bright_cyan());
     println!("{}", "    DISTRIBUTED SWARM DEMONSTRATION".bright_cyan().bold());
     println!("{}", "═══════════════════════════════════════════".bright_cyan());
@@ -76,16 +79,16 @@ async fn main() {
     let mut parent = Subgraph::new();
     let children = parent.split(3).await;
     
-    println!("{}", format!("Parent subgraph {} split into {} children", 
+    println!("Parent subgraph {} split into {} children", 
         parent.id.to_string()[..8].bright_cyan(),
         children.len()
-    ));
+    );
     
     for (i, child) in children.iter().enumerate() {
-        println!("{}", format!("  └─ Child {}: {}", 
+        println!("  └─ Child {}: {}", 
             i + 1, 
             child.id.to_string()[..8].bright_green()
-        ));
+        );
     }
     
     println!();
@@ -96,7 +99,7 @@ async fn main() {
     let complex_prompt = "The quick brown fox jumps over the lazy dog while thinking about distributed computation in tornado-like swarms";
     
     println!("{}", "📝 Sending complex prompt through distributed swarm...".bright_magenta());
-    let prompt_id = processor.send_prompt(complex_prompt).await;
+    let prompt_id = processor.send_prompt(complex_prompt).await.expect("swarm has tornadoes");
     
     // Simulate distributed processing with status updates
     for step in 0..10 {
@@ -5,6 +5,9 @@ use colored::*;
 /// Example demonstrating language model decomposition and swarm processing
 #[tokio::main]
 async fn main() {
+    // Honour NO_COLOR / WINGBEAT_OUTPUT and report swarm activity through tracing
+    wingbeat::output::init_from_env();
+    
     println!("{}", "╔══════════════════════════════════════════╗".bright_cyan());
     println!("{}", "║    LANGUAGE MODEL SWARM PROCESSING       ║".bright_cyan().bold());
     println!("{}", "╚══════════════════════════════════════════╝".bright_cyan());
@@ -17,16 +20,16 @@ async fn main() {
     let mut processor = EnhancedProcessor::new(swarm);
     
     // Example prompts to process
-    let prompts = vec![
+    let prompts = [
         "Explain quantum computing in simple terms",
         "Write a haiku about artificial intelligence",
         "What is the meaning of life?",
     ];
     
-    let strategies = vec![
+    let strategies = [
         DecompositionStrategy::LayerWise,
         DecompositionStrategy::AttentionHeads,
-        DecompositionStrategy::TokenWise,
+        DecompositionStrategy::TokenWise { chunks: 4 },
     ];
     
     for (i, (prompt, strategy)) in prompts.iter().zip(strategies.iter()).enumerate() {
@@ -36,7 +39,10 @@ async fn main() {
         println!();
         
         // Process the prompt through the decomposed model
-        let result = processor.process_with_model(prompt, strategy.clone()).await;
+        match processor.process_with_model(prompt, strategy.clone()).await {
+            Ok(result) => println!("{}", result.to_string().bright_cyan()),
+            Err(e) => println!("{}", format!("❌ Processing failed: {}", e).red()),
+        }
         
         println!();
         println!("{}", "───────────────────────────────────────────".bright_black());
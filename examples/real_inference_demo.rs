@@ -1,12 +1,16 @@
 use wingbeat::{
     InferenceEngine, TornadoSwarm, EnhancedProcessor,
     ModelDecomposer, DecompositionStrategy,
-    Tensor, LayerOperation,
+    Tensor, TensorOps, Vec3,
 };
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Honour NO_COLOR / WINGBEAT_OUTPUT and report swarm activity through tracing
+    wingbeat::output::init_from_env();
+    
     println!("🌪️  Wingbeat Real Inference Demo");
     println!("================================\n");
 
@@ -24,24 +28,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize the swarm
     println!("🌪️  Initializing tornado swarm...");
-    let mut swarm = TornadoSwarm::new(8); // 8 tornadoes
-    swarm.initialize();
-    println!("   Swarm initialized with {} tornadoes", swarm.tornadoes.len());
+    let swarm = Arc::new(TornadoSwarm::new());
+    for i in 0..8 { // 8 tornadoes
+        swarm.spawn_tornado(Vec3::new((i as f32) * 10.0, 0.0, 0.0)).await;
+    }
+    println!("   Swarm initialized with {} tornadoes", swarm.tornadoes.read().await.len());
     println!();
 
     // Create model decomposer
     println!("🔍 Creating model decomposer...");
-    let decomposer = ModelDecomposer::new(
-        "transformer".to_string(),
-        DecompositionStrategy::LayerWise,
-        HashMap::new(),
-    );
+    let mut decomposer = ModelDecomposer::new();
+    decomposer.create_sample_model();
+    decomposer.strategy = DecompositionStrategy::LayerWise;
     println!("   Decomposer created with {:?} strategy", DecompositionStrategy::LayerWise);
     println!();
 
     // Create enhanced processor
     println!("⚡ Creating enhanced processor...");
-    let mut processor = EnhancedProcessor::new(swarm, decomposer);
+    let mut processor = EnhancedProcessor::with_decomposer(swarm, decomposer);
     println!("   Enhanced processor ready");
     println!();
 
@@ -73,6 +77,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let context = wingbeat::LayerContext {
             input: test_input.clone(),
             output: None,
+            attention_mask: None,
             metadata: HashMap::new(),
         };
         
@@ -91,7 +96,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Test swarm processing with real inference
     println!("🌪️  Testing swarm processing with real inference...");
-    let test_prompts = vec![
+    let test_prompts = [
         "Hello world",
         "The quick brown fox",
         "Wingbeat is amazing",
@@ -104,20 +109,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let result = processor.process_prompt(prompt).await?;
         
         println!("   Swarm result: {:?}", result.status);
-        if let Some(output) = result.output {
-            println!("   Output: {}", output);
-        }
+        println!("   Output: {}", result);
         println!();
     }
 
     // Test model decomposition with real weights
     println!("🔍 Testing model decomposition with real weights...");
-    let subgraphs = processor.decomposer.decompose_model(&engine.weights)?;
+    let subgraphs = processor.decomposer.decompose_model(DecompositionStrategy::LayerWise).await;
     println!("   Decomposed into {} subgraphs", subgraphs.len());
     
     for (i, subgraph) in subgraphs.iter().enumerate() {
         println!("   Subgraph {}: {:?} with {} nodes", 
-            i, subgraph.subgraph_type, subgraph.nodes.len());
+            i, subgraph.subgraph_type, subgraph.graph.read().await.node_count());
     }
     println!();
 
@@ -139,8 +142,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("   3. Implement efficient tensor operations (BLAS, etc.)");
     println!("   4. Add proper tokenization (BPE, SentencePiece, etc.)");
     println!("   5. Implement proper sampling strategies");
-    println!("   6. Add model checkpointing and loading");
-    println!("   7. Optimize for GPU/TPU acceleration");
+    println!("   6. Optimize for GPU/TPU acceleration");
 
     Ok(())
 } 
\ No newline at end of file
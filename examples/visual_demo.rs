@@ -7,6 +7,9 @@ use tokio::sync::RwLock;
 /// Visual demonstration of tornado swarm dynamics
 #[tokio::main]
 async fn main() {
+    // Honour NO_COLOR / WINGBEAT_OUTPUT and report swarm activity through tracing
+    wingbeat::output::init_from_env();
+    
     println!("{}", "╔══════════════════════════════════════════╗".bright_magenta());
     println!("{}", "║      TORNADO SWARM VISUALIZATION         ║".bright_magenta().bold());
     println!("{}", "╚══════════════════════════════════════════╝".bright_magenta());
@@ -16,7 +19,7 @@ async fn main() {
     let swarm = Arc::new(TornadoSwarm::new());
     
     // Spawn tornadoes in a pattern
-    let positions = vec![
+    let positions = [
         Vec3::new(-20.0, -20.0, 0.0),
         Vec3::new(20.0, -20.0, 0.0),
         Vec3::new(0.0, 20.0, 0.0),
@@ -67,7 +70,7 @@ async fn main() {
         // Show tornado positions
         let tornadoes = swarm.tornadoes.read().await;
         for (i, tornado) in tornadoes.iter().enumerate() {
-            let subgraph_count = tornado.subgraphs.read().await.len();
+            let subgraph_count = tornado.subgraph_count().await;
             
             println!("{}", format!(
                 "  Tornado {}: Position ({:.1}, {:.1}, {:.1}) | {} subgraphs",
@@ -107,38 +110,34 @@ async fn main() {
     println!();
     
     // Show final statistics
+    let total_subgraphs = swarm.total_subgraphs().await;
     let tornadoes = swarm.tornadoes.read().await;
-    let total_subgraphs: usize = futures::future::join_all(
-        tornadoes.iter().map(|t| async {
-            t.subgraphs.read().await.len()
-        })
-    ).await.iter().sum();
     
     println!("{}", "📊 Final Statistics:".bright_yellow());
-    println!("{}", format!("  • Total Tornadoes: {}", tornadoes.len()));
-    println!("{}", format!("  • Total Subgraphs: {}", total_subgraphs));
-    println!("{}", format!("  • Average Subgraphs per Tornado: {:.1}", 
+    println!("  • Total Tornadoes: {}", tornadoes.len());
+    println!("  • Total Subgraphs: {}", total_subgraphs);
+    println!("  • Average Subgraphs per Tornado: {:.1}", 
         total_subgraphs as f32 / tornadoes.len() as f32
-    ));
+    );
 }
 
 async fn print_swarm_visualization(step: usize) {
-    let frames = vec![
-        vec![
+    let frames = [
+        [
             "     🌪️           🌀           🌪️     ",
             "    / \\          / \\          / \\    ",
             "   🧩 🧩        🧩 🧩        🧩 🧩   ",
             "    \\ /          \\ /          \\ /    ",
             "     🌊           🌊           🌊     ",
         ],
-        vec![
+        [
             "      🌀         🌪️         🌀      ",
             "     / \\        /|\\        / \\     ",
             "    🧩🧩🧩    🧩 🧩 🧩    🧩🧩🧩    ",
             "     \\ /        \\|/        \\ /     ",
             "      🌊         🌊         🌊      ",
         ],
-        vec![
+        [
             "    🌪️     🌀     🌪️     🌀    ",
             "    |\\     /|     |\\     /|    ",
             "   🧩 🧩 🧩 🧩 🧩 🧩 🧩 🧩   ",
@@ -1,131 +1,212 @@
 use crate::{
-    swarm::tornado::TornadoSwarm,
+    swarm::tornado::{TornadoSwarm, Vec3},
     computation::model_decomposer::{ModelDecomposer, DecompositionStrategy},
-    core::subgraph::{Subgraph, SubgraphId, SubgraphType},
-    inference::{ModelWeights, Tensor, LayerOperation},
+    core::subgraph::{Subgraph, SubgraphType, ComputeNode, Operation, NodeState},
+    error::WingbeatError,
+    inference::{InferenceEngine, ModelWeights},
 };
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::RwLock;
 use uuid::Uuid;
+use tracing::{debug, info, instrument};
 
 /// Enhanced processor that integrates model decomposition with swarm processing
 pub struct EnhancedProcessor {
-    pub swarm: TornadoSwarm,
+    pub swarm: Arc<TornadoSwarm>,
     pub decomposer: ModelDecomposer,
+    /// Keep distributed subgraphs resident in the swarm between `process_prompt` calls,
+    /// decomposing again only when the model or strategy changes
+    pub warm_pool: bool,
+    resident: Option<WarmPool>,
+}
+
+/// Subgraphs left in the swarm by a warm `process_prompt` call
+struct WarmPool {
+    key: DecompositionKey,
+    subgraphs: Vec<Subgraph>,
+}
+
+/// What a decomposition depends on; a pool is reused only while this is unchanged
+#[derive(PartialEq)]
+struct DecompositionKey {
+    strategy: DecompositionStrategy,
+    layer_ids: Vec<Uuid>,
+    /// Only `TokenWise` subgraphs depend on the prompt length
+    token_count: Option<usize>,
 }
 
 impl EnhancedProcessor {
-    pub fn new(swarm: TornadoSwarm, decomposer: ModelDecomposer) -> Self {
-        Self { swarm, decomposer }
+    /// Create a processor backed by the sample language model
+    pub fn new(swarm: Arc<TornadoSwarm>) -> Self {
+        let mut decomposer = ModelDecomposer::new();
+        decomposer.create_sample_model();
+        Self::with_decomposer(swarm, decomposer)
+    }
+
+    /// Create a processor with a caller-supplied decomposer
+    pub fn with_decomposer(swarm: Arc<TornadoSwarm>, decomposer: ModelDecomposer) -> Self {
+        Self { swarm, decomposer, warm_pool: false, resident: None }
+    }
+
+    /// Keep subgraphs resident between calls instead of releasing them after each prompt
+    pub fn with_warm_pool(mut self, warm_pool: bool) -> Self {
+        self.warm_pool = warm_pool;
+        self
+    }
+
+    fn decomposition_key(&self) -> DecompositionKey {
+        let strategy = self.decomposer.strategy.clone();
+        let token_count = match strategy {
+            DecompositionStrategy::TokenWise { .. } => Some(self.decomposer.token_count),
+            _ => None,
+        };
+        DecompositionKey {
+            strategy,
+            layer_ids: self.decomposer.model_layers.iter().map(|layer| layer.id).collect(),
+            token_count,
+        }
+    }
+
+    /// Process a prompt after switching the decomposer to the given strategy
+    pub async fn process_with_model(
+        &mut self,
+        prompt: &str,
+        strategy: DecompositionStrategy,
+    ) -> Result<SwarmPromptResult, String> {
+        self.decomposer.strategy = strategy;
+        self.process_prompt(prompt).await
     }
 
     /// Process a prompt through the swarm with model decomposition
+    #[instrument(skip(self), fields(strategy = ?self.decomposer.strategy))]
     pub async fn process_prompt(&mut self, prompt: &str) -> Result<SwarmPromptResult, String> {
-        println!("🌪️  Processing prompt: '{}'", prompt);
+        info!("processing prompt");
         
-        // Decompose the model into subgraphs
-        let subgraphs = self.decomposer.decompose_model(&ModelWeights::new())?;
-        println!("   Decomposed into {} subgraphs", subgraphs.len());
+        self.decomposer.token_count = prompt.split_whitespace().count();
+        self.ensure_tornadoes().await?;
+        let key = self.decomposition_key();
+        let tornadoes = self.swarm.tornadoes.read().await;
         
-        // Distribute subgraphs across the swarm
-        let mut distributed_subgraphs = Vec::new();
-        for (i, subgraph) in subgraphs.iter().enumerate() {
-            let tornado = &mut self.swarm.tornadoes[i % self.swarm.tornadoes.len()];
-            tornado.sweep_up_subgraph(subgraph.clone());
-            distributed_subgraphs.push(subgraph.clone());
-            println!("   Subgraph {} distributed to tornado {}", i, tornado.id);
-        }
+        let distributed_subgraphs = match self.resident.take() {
+            // The model is already resident in the swarm
+            Some(pool) if self.warm_pool && pool.key == key => {
+                debug!(subgraph_count = pool.subgraphs.len(), "reusing resident subgraphs");
+                pool.subgraphs
+            },
+            stale => {
+                // Evict subgraphs left behind for a different model
+                for subgraph in stale.into_iter().flat_map(|pool| pool.subgraphs) {
+                    for tornado in tornadoes.iter() {
+                        tornado.release_by_id(subgraph.id).await;
+                    }
+                }
+                
+                // Decompose the model into subgraphs
+                let subgraphs = self.decomposer.decompose_model(key.strategy.clone()).await;
+                debug!(subgraph_count = subgraphs.len(), "model decomposed");
+                
+                // Distribute subgraphs across the swarm
+                for (i, subgraph) in subgraphs.iter().enumerate() {
+                    let tornado = &tornadoes[i % tornadoes.len()];
+                    tornado.sweep_up(Arc::new(RwLock::new(subgraph.clone()))).await;
+                    debug!(subgraph_id = %subgraph.id, tornado_id = %tornado.id, "subgraph distributed");
+                }
+                subgraphs
+            },
+        };
         
         // Simulate computation in the swarm
-        println!("   🌀 Spinning tornadoes...");
-        for tornado in &mut self.swarm.tornadoes {
-            tornado.spin();
+        debug!(tornado_count = tornadoes.len(), "spinning tornadoes");
+        for tornado in tornadoes.iter() {
+            tornado.spin().await;
         }
         
         // Process the prompt through each subgraph
-        let mut results = Vec::new();
-        for (i, subgraph) in distributed_subgraphs.iter().enumerate() {
-            let result = self.process_subgraph(subgraph, prompt).await?;
-            results.push(result);
-            println!("   Subgraph {} processed: {:?}", i, subgraph.subgraph_type);
+        let mut per_subgraph = Vec::new();
+        for subgraph in &distributed_subgraphs {
+            per_subgraph.push(SubgraphResult {
+                id: subgraph.id,
+                subgraph_type: subgraph.subgraph_type.clone(),
+                output: subgraph.execute(prompt).await?,
+            });
+            debug!(subgraph_id = %subgraph.id, subgraph_type = ?subgraph.subgraph_type, "subgraph processed");
         }
         
         // Reintegrate results
-        let final_result = self.reintegrate_results(results, prompt).await?;
-        println!("   ✅ Results reintegrated");
+        let final_output = format!("'{}' processed through {} subgraphs", prompt, per_subgraph.len());
+        info!(result_count = per_subgraph.len(), "results reintegrated");
         
-        // Release subgraphs back to the swarm
-        for tornado in &mut self.swarm.tornadoes {
-            tornado.release_subgraphs();
+        // Release subgraphs back to the swarm, or leave them resident for the next prompt
+        if self.warm_pool {
+            self.resident = Some(WarmPool { key, subgraphs: distributed_subgraphs });
+        } else {
+            for tornado in tornadoes.iter() {
+                let held = tornado.subgraphs.read().await.len();
+                tornado.release(held).await;
+            }
         }
         
         Ok(SwarmPromptResult {
             status: PromptStatus::Completed,
-            output: Some(final_result),
+            prompt: prompt.to_string(),
+            per_subgraph,
+            final_output,
             metadata: HashMap::new(),
         })
     }
 
-    /// Process a single subgraph
-    async fn process_subgraph(&self, subgraph: &Subgraph, prompt: &str) -> Result<String, String> {
-        // Simulate processing based on subgraph type
-        match subgraph.subgraph_type {
-            SubgraphType::Embedding => {
-                Ok(format!("[Embedded: {}]", prompt))
-            }
-            SubgraphType::Attention => {
-                Ok(format!("[Attended: {}]", prompt))
-            }
-            SubgraphType::FeedForward => {
-                Ok(format!("[Processed: {}]", prompt))
-            }
-            SubgraphType::Output => {
-                Ok(format!("[Output: {}]", prompt))
-            }
-            SubgraphType::Custom(_) => {
-                Ok(format!("[Custom: {}]", prompt))
-            }
+    /// Spawn a default set of tornadoes if the swarm is empty
+    async fn ensure_tornadoes(&self) -> Result<(), String> {
+        if !self.swarm.tornadoes.read().await.is_empty() {
+            return Ok(());
         }
-    }
-
-    /// Reintegrate results from all subgraphs
-    async fn reintegrate_results(&self, results: Vec<String>, original_prompt: &str) -> Result<String, String> {
-        let mut final_output = format!("🌪️ Swarm processed: '{}'\n", original_prompt);
-        final_output.push_str("📊 Results from subgraphs:\n");
         
-        for (i, result) in results.iter().enumerate() {
-            final_output.push_str(&format!("   Subgraph {}: {}\n", i, result));
+        for i in 0..3 {
+            let pos = Vec3::new(
+                (i as f32) * 10.0,
+                (i as f32) * 5.0,
+                0.0
+            );
+            self.swarm.spawn_tornado(pos).await;
         }
         
-        final_output.push_str("🎯 Final integrated response: ");
-        final_output.push_str(&format!("'{}' processed through {} subgraphs", 
-            original_prompt, results.len()));
-        
-        Ok(final_output)
+        if self.swarm.tornadoes.read().await.is_empty() {
+            return Err("No tornadoes available in the swarm".to_string());
+        }
+        Ok(())
     }
 
     /// Run distributed inference with real model weights
+    #[instrument(skip(self, weights), fields(strategy = ?self.decomposer.strategy))]
     pub async fn run_distributed_inference(
         &mut self, 
         prompt: &str, 
         weights: &ModelWeights
     ) -> Result<String, String> {
-        println!("🚀 Running distributed inference for: '{}'", prompt);
+        info!("running distributed inference");
         
         // Decompose model with real weights
-        let subgraphs = self.decomposer.decompose_model(weights)?;
-        println!("   Model decomposed into {} subgraphs", subgraphs.len());
+        self.decomposer.token_count = prompt.split_whitespace().count();
+        let strategy = self.decomposer.strategy.clone();
+        let subgraphs = self.decomposer.decompose_model(strategy).await;
+        debug!(subgraph_count = subgraphs.len(), "model decomposed");
+        
+        self.ensure_tornadoes().await?;
+        let tornadoes = self.swarm.tornadoes.read().await;
         
         // Distribute across swarm
         let mut distributed_results = Vec::new();
         for (i, subgraph) in subgraphs.iter().enumerate() {
-            let tornado = &mut self.swarm.tornadoes[i % self.swarm.tornadoes.len()];
-            tornado.sweep_up_subgraph(subgraph.clone());
+            let tornado = &tornadoes[i % tornadoes.len()];
+            tornado.sweep_up(Arc::new(RwLock::new(subgraph.clone()))).await;
             
             // Simulate inference on this subgraph
             let result = self.simulate_inference_on_subgraph(subgraph, prompt, weights).await?;
             distributed_results.push(result);
             
-            tornado.release_subgraphs();
+            tornado.release(1).await;
         }
         
         // Combine results
@@ -134,6 +215,45 @@ impl EnhancedProcessor {
         Ok(final_result)
     }
 
+    /// Run `engine`'s forward pass with each layer hosted by a subgraph in a tornado,
+    /// carrying the activation tensor from tornado to tornado
+    #[instrument(skip(self, engine))]
+    pub async fn infer_distributed(
+        &mut self,
+        engine: &InferenceEngine,
+        prompt: &str,
+    ) -> Result<String, WingbeatError> {
+        self.ensure_tornadoes().await.map_err(|_| WingbeatError::NoTornadoes)?;
+        let tornadoes = self.swarm.tornadoes.read().await;
+        
+        let mut activation = engine.prepare_input(prompt)?;
+        for (layer_index, layer_op) in engine.layer_operations.iter().enumerate() {
+            let layer_type = layer_op.layer_type();
+            let subgraph = Subgraph::with_type(SubgraphType::from(&layer_type));
+            subgraph.graph.write().await.add_node(ComputeNode {
+                id: Uuid::new_v4(),
+                operation: Operation::Process(format!("{:?}", layer_type)),
+                state: NodeState::Idle,
+                metadata: HashMap::from([
+                    ("layer_id".to_string(), layer_op.layer_id().to_string()),
+                    ("layer_index".to_string(), layer_index.to_string()),
+                ]),
+            });
+            
+            // Host the layer in a tornado while it computes
+            let tornado = &tornadoes[layer_index % tornadoes.len()];
+            let subgraph_id = subgraph.id;
+            tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+            
+            activation = engine.run_layer(layer_index, &activation)?;
+            debug!(layer_index, tornado_id = %tornado.id, shape = ?activation.shape, "layer computed");
+            
+            tornado.release_by_id(subgraph_id).await;
+        }
+        
+        engine.decode_output(&activation)
+    }
+
     /// Simulate inference on a specific subgraph
     async fn simulate_inference_on_subgraph(
         &self, 
@@ -187,14 +307,53 @@ impl EnhancedProcessor {
     }
 }
 
+/// Output of one distributed subgraph
+#[derive(Debug, Clone)]
+pub struct SubgraphResult {
+    pub id: Uuid,
+    pub subgraph_type: SubgraphType,
+    pub output: String,
+}
+
+impl fmt::Display for SubgraphResult {
+    /// Label the output with the model component that produced it
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.subgraph_type {
+            SubgraphType::Embedding => "Embedded",
+            SubgraphType::Attention => "Attended",
+            SubgraphType::FeedForward => "Processed",
+            SubgraphType::Output => "Output",
+            SubgraphType::Custom(_) => "Custom",
+        };
+        write!(f, "[{}: {}]", label, self.output)
+    }
+}
+
 /// Result of swarm prompt processing
 #[derive(Debug)]
 pub struct SwarmPromptResult {
     pub status: PromptStatus,
-    pub output: Option<String>,
+    pub prompt: String,
+    /// One entry per distributed subgraph, in distribution order
+    pub per_subgraph: Vec<SubgraphResult>,
+    pub final_output: String,
     pub metadata: HashMap<String, String>,
 }
 
+impl fmt::Display for SwarmPromptResult {
+    /// The human-readable report, one line per subgraph
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🌪️ Swarm processed: '{}'", self.prompt)?;
+        writeln!(f, "📊 Results from subgraphs:")?;
+        
+        for (i, result) in self.per_subgraph.iter().enumerate() {
+            writeln!(f, "   Subgraph {}: {}", i, result)?;
+        }
+        
+        write!(f, "🎯 Final integrated response: {}", self.final_output)
+    }
+}
+
 /// Status of prompt processing
 #[derive(Debug)]
 pub enum PromptStatus {
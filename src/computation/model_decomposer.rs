@@ -1,9 +1,11 @@
-use crate::core::subgraph::{Subgraph, ComputeNode, Operation, NodeState};
+use crate::core::subgraph::{Subgraph, SubgraphType, ComputeNode, Operation, NodeState};
+use crate::error::WingbeatError;
+use crate::inference::tensor_ops::{Tensor, TensorOps};
+use crate::inference::config::TransformerConfig;
 use std::collections::HashMap;
+use tracing::info;
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
-use std::sync::Arc;
-use tokio::sync::RwLock;
 
 /// Represents a language model layer or component
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,13 +24,36 @@ pub enum LayerType {
     FeedForward,
     Embedding,
     Output,
+    Pooling,
     Custom(String),
 }
 
+impl From<&LayerType> for SubgraphType {
+    fn from(layer_type: &LayerType) -> Self {
+        match layer_type {
+            LayerType::Attention => SubgraphType::Attention,
+            LayerType::FeedForward => SubgraphType::FeedForward,
+            LayerType::Embedding => SubgraphType::Embedding,
+            LayerType::Output => SubgraphType::Output,
+            LayerType::Pooling => SubgraphType::Custom("pooling".to_string()),
+            LayerType::Custom(name) => SubgraphType::Custom(name.clone()),
+        }
+    }
+}
+
 /// Manages the decomposition of language models into subgraphs
 pub struct ModelDecomposer {
     pub model_layers: Vec<ModelLayer>,
     pub subgraph_mapping: HashMap<Uuid, Uuid>, // layer_id -> subgraph_id
+    pub subgraph_metadata: HashMap<Uuid, HashMap<String, String>>, // subgraph_id -> node metadata
+    pub strategy: DecompositionStrategy,
+    /// Head count for attention layers that don't set a "num_heads" parameter
+    pub num_heads: usize,
+    /// Length of the prompt being processed, used to compute token ranges for `TokenWise`
+    pub token_count: usize,
+    /// Derive subgraph and node ids from the layer id and piece index (UUID v5) instead of
+    /// generating random ones, so decomposing the same model twice gives identical subgraphs
+    pub deterministic_ids: bool,
 }
 
 impl ModelDecomposer {
@@ -36,6 +61,31 @@ impl ModelDecomposer {
         Self {
             model_layers: Vec::new(),
             subgraph_mapping: HashMap::new(),
+            subgraph_metadata: HashMap::new(),
+            strategy: DecompositionStrategy::LayerWise,
+            num_heads: TransformerConfig::default().num_heads,
+            token_count: 0,
+            deterministic_ids: false,
+        }
+    }
+
+    /// Subgraph for the `index`-th piece of `layer`
+    fn layer_subgraph(&self, layer: &ModelLayer, index: usize, subgraph_type: SubgraphType) -> Subgraph {
+        if !self.deterministic_ids {
+            return Subgraph::with_type(subgraph_type);
+        }
+        
+        let mut subgraph = Subgraph::with_id(Uuid::new_v5(&layer.id, &index.to_le_bytes()));
+        subgraph.subgraph_type = subgraph_type;
+        subgraph
+    }
+
+    /// Id for the compute node placed in `subgraph_id`
+    fn node_id(&self, subgraph_id: Uuid) -> Uuid {
+        if self.deterministic_ids {
+            Uuid::new_v5(&subgraph_id, b"node")
+        } else {
+            Uuid::new_v4()
         }
     }
 
@@ -86,19 +136,53 @@ impl ModelDecomposer {
         layers
     }
 
+    /// Check that every layer's input size matches the output size of the layers it depends on
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        
+        for (index, layer) in self.model_layers.iter().enumerate() {
+            for dependency_id in &layer.dependencies {
+                match self.model_layers.iter().position(|l| l.id == *dependency_id) {
+                    Some(dep_index) => {
+                        let dependency = &self.model_layers[dep_index];
+                        if dependency.output_size != layer.input_size {
+                            errors.push(format!(
+                                "Layer {} ({:?}) expects input size {} but layer {} ({:?}) outputs {}",
+                                index, layer.layer_type, layer.input_size,
+                                dep_index, dependency.layer_type, dependency.output_size
+                            ));
+                        }
+                    }
+                    None => errors.push(format!(
+                        "Layer {} ({:?}) depends on unknown layer {}",
+                        index, layer.layer_type, dependency_id
+                    )),
+                }
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Decompose the model into subgraphs
     pub async fn decompose_model(&mut self, decomposition_strategy: DecompositionStrategy) -> Vec<Subgraph> {
         let mut subgraphs = Vec::new();
+        self.subgraph_mapping.clear();
+        self.subgraph_metadata.clear();
         
         match decomposition_strategy {
             DecompositionStrategy::LayerWise => {
                 // Each layer becomes its own subgraph
                 for layer in &self.model_layers {
-                    let subgraph = Subgraph::new();
+                    let subgraph = self.layer_subgraph(layer, 0, SubgraphType::from(&layer.layer_type));
                     
                     // Create compute node for this layer
                     let node = ComputeNode {
-                        id: Uuid::new_v4(),
+                        id: self.node_id(subgraph.id),
                         operation: Operation::Process(format!("{:?}", layer.layer_type)),
                         state: NodeState::Idle,
                         metadata: HashMap::from([
@@ -109,6 +193,7 @@ impl ModelDecomposer {
                         ]),
                     };
                     
+                    self.subgraph_metadata.insert(subgraph.id, node.metadata.clone());
                     subgraph.graph.write().await.add_node(node);
                     self.subgraph_mapping.insert(layer.id, subgraph.id);
                     subgraphs.push(subgraph);
@@ -120,30 +205,35 @@ impl ModelDecomposer {
                 for layer in &self.model_layers {
                     match layer.layer_type {
                         LayerType::Attention => {
-                            // Create multiple subgraphs for attention heads
-                            for head in 0..8 { // 8 attention heads
-                                let subgraph = Subgraph::new();
+                            // Create one subgraph per attention head
+                            let head_count = layer.parameters.get("num_heads")
+                                .map(|&heads| heads as usize)
+                                .filter(|&heads| heads > 0)
+                                .unwrap_or(self.num_heads);
+                            for head in 0..head_count {
+                                let subgraph = self.layer_subgraph(layer, head, SubgraphType::Attention);
                                 
                                 let node = ComputeNode {
-                                    id: Uuid::new_v4(),
+                                    id: self.node_id(subgraph.id),
                                     operation: Operation::Process(format!("Attention_Head_{}", head)),
                                     state: NodeState::Idle,
                                     metadata: HashMap::from([
                                         ("layer_id".to_string(), layer.id.to_string()),
                                         ("head_index".to_string(), head.to_string()),
-                                        ("head_count".to_string(), "8".to_string()),
+                                        ("head_count".to_string(), head_count.to_string()),
                                     ]),
                                 };
                                 
+                                self.subgraph_metadata.insert(subgraph.id, node.metadata.clone());
                                 subgraph.graph.write().await.add_node(node);
                                 subgraphs.push(subgraph);
                             }
                         },
                         _ => {
                             // Other layers as single subgraphs
-                            let subgraph = Subgraph::new();
+                            let subgraph = self.layer_subgraph(layer, 0, SubgraphType::from(&layer.layer_type));
                             let node = ComputeNode {
-                                id: Uuid::new_v4(),
+                                id: self.node_id(subgraph.id),
                                 operation: Operation::Process(format!("{:?}", layer.layer_type)),
                                 state: NodeState::Idle,
                                 metadata: HashMap::from([
@@ -151,6 +241,7 @@ impl ModelDecomposer {
                                 ]),
                             };
                             
+                            self.subgraph_metadata.insert(subgraph.id, node.metadata.clone());
                             subgraph.graph.write().await.add_node(node);
                             subgraphs.push(subgraph);
                         }
@@ -158,28 +249,76 @@ impl ModelDecomposer {
                 }
             },
             
-            DecompositionStrategy::TokenWise => {
-                // Split processing by tokens
+            DecompositionStrategy::TokenWise { chunks } => {
+                // Split the prompt's tokens into contiguous chunks
+                let chunks = chunks.max(1);
                 for layer in &self.model_layers {
-                    let token_chunks = 4; // Process in chunks of tokens
-                    
-                    for chunk in 0..token_chunks {
-                        let subgraph = Subgraph::new();
+                    for chunk in 0..chunks {
+                        let subgraph = self.layer_subgraph(layer, chunk, SubgraphType::from(&layer.layer_type));
+                        let (token_start, token_end) = split_range(self.token_count, chunk, chunks);
                         
                         let node = ComputeNode {
-                            id: Uuid::new_v4(),
+                            id: self.node_id(subgraph.id),
                             operation: Operation::Process(format!("{:?}_TokenChunk_{}", layer.layer_type, chunk)),
                             state: NodeState::Idle,
                             metadata: HashMap::from([
                                 ("layer_id".to_string(), layer.id.to_string()),
                                 ("chunk_index".to_string(), chunk.to_string()),
-                                ("total_chunks".to_string(), token_chunks.to_string()),
+                                ("total_chunks".to_string(), chunks.to_string()),
+                                ("token_start".to_string(), token_start.to_string()),
+                                ("token_end".to_string(), token_end.to_string()),
+                            ]),
+                        };
+                        
+                        self.subgraph_metadata.insert(subgraph.id, node.metadata.clone());
+                        subgraph.graph.write().await.add_node(node);
+                        subgraphs.push(subgraph);
+                    }
+                }
+            }
+
+            DecompositionStrategy::TensorParallel { shards } => {
+                // Split each layer's weight columns across shards, then aggregate the partial outputs
+                let shards = shards.max(1);
+                for layer in &self.model_layers {
+                    for shard in 0..shards {
+                        let subgraph = self.layer_subgraph(layer, shard, SubgraphType::from(&layer.layer_type));
+                        let (column_start, column_end) = split_range(layer.output_size, shard, shards);
+                        
+                        let node = ComputeNode {
+                            id: self.node_id(subgraph.id),
+                            operation: Operation::Process(format!("{:?}_Shard_{}", layer.layer_type, shard)),
+                            state: NodeState::Idle,
+                            metadata: HashMap::from([
+                                ("layer_id".to_string(), layer.id.to_string()),
+                                ("shard_index".to_string(), shard.to_string()),
+                                ("shard_count".to_string(), shards.to_string()),
+                                ("column_start".to_string(), column_start.to_string()),
+                                ("column_end".to_string(), column_end.to_string()),
                             ]),
                         };
                         
+                        self.subgraph_metadata.insert(subgraph.id, node.metadata.clone());
                         subgraph.graph.write().await.add_node(node);
                         subgraphs.push(subgraph);
                     }
+                    
+                    // Aggregation node concatenates the column shards back together
+                    let subgraph = self.layer_subgraph(layer, shards, SubgraphType::from(&layer.layer_type));
+                    let node = ComputeNode {
+                        id: self.node_id(subgraph.id),
+                        operation: Operation::Aggregate,
+                        state: NodeState::Idle,
+                        metadata: HashMap::from([
+                            ("layer_id".to_string(), layer.id.to_string()),
+                            ("aggregation".to_string(), "concat_columns".to_string()),
+                            ("shard_count".to_string(), shards.to_string()),
+                        ]),
+                    };
+                    
+                    self.subgraph_metadata.insert(subgraph.id, node.metadata.clone());
+                    subgraph.graph.write().await.add_node(node);
+                    subgraphs.push(subgraph);
                 }
             }
         }
@@ -189,21 +328,14 @@ impl ModelDecomposer {
 
     /// Reintegrate results from subgraphs back into a coherent model output
     pub async fn reintegrate_results(&self, subgraph_results: HashMap<Uuid, String>) -> String {
-        println!("🔄 Reintegrating model results from {} subgraphs...", subgraph_results.len());
+        info!(subgraph_count = subgraph_results.len(), "🔄 Reintegrating model results");
         
         // Sort results by layer order and chunk/head indices
-        let mut sorted_results: Vec<(String, usize)> = Vec::new();
+        let mut sorted_results: Vec<(String, (usize, usize))> = Vec::new();
         
         for (subgraph_id, result) in subgraph_results {
-            if let Some(layer_id) = self.find_layer_for_subgraph(subgraph_id) {
-                if let Some(_layer) = self.model_layers.iter().find(|l| l.id == layer_id) {
-                    let layer_index = self.model_layers.iter().position(|l| l.id == layer_id).unwrap();
-                    
-                    // Extract chunk/head index from metadata if present
-                    let chunk_index = 0; // Default for non-chunked layers
-                    
-                    sorted_results.push((result, layer_index * 1000 + chunk_index));
-                }
+            if let Some(sort_key) = self.sort_key_for_subgraph(subgraph_id) {
+                sorted_results.push((result, sort_key));
             }
         }
         
@@ -217,23 +349,113 @@ impl ModelDecomposer {
             .collect::<Vec<_>>()
             .join(" ");
         
-        println!("✅ Model reintegration complete!");
+        info!("✅ Model reintegration complete");
         combined_result
     }
 
-    fn find_layer_for_subgraph(&self, subgraph_id: Uuid) -> Option<Uuid> {
-        for (layer_id, sg_id) in &self.subgraph_mapping {
-            if *sg_id == subgraph_id {
-                return Some(*layer_id);
+    /// Column slice of a layer's `[input_size, output_size]` weight that the tensor-parallel
+    /// shard `subgraph_id` is responsible for
+    pub fn shard_weight(&self, subgraph_id: Uuid, weight: &Tensor) -> Result<Tensor, WingbeatError> {
+        let metadata = self.subgraph_metadata.get(&subgraph_id)
+            .filter(|metadata| metadata.contains_key("shard_index"))
+            .ok_or_else(|| WingbeatError::ParameterNotFound(format!("shard metadata for subgraph {}", subgraph_id)))?;
+        let column = |key: &str| -> Result<usize, WingbeatError> {
+            metadata.get(key)
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| WingbeatError::ParameterNotFound(format!("{} for subgraph {}", key, subgraph_id)))
+        };
+        
+        // Every shard of the layer must see the same full weight, or the columns won't line up
+        let layer = metadata.get("layer_id")
+            .and_then(|id| id.parse::<Uuid>().ok())
+            .and_then(|id| self.model_layers.iter().find(|layer| layer.id == id))
+            .ok_or_else(|| WingbeatError::ParameterNotFound(format!("layer for subgraph {}", subgraph_id)))?;
+        let expected = vec![layer.input_size, layer.output_size];
+        if weight.shape != expected {
+            return Err(WingbeatError::ShapeMismatch { expected, got: weight.shape.clone() });
+        }
+        
+        weight.slice_dim(1, column("column_start")?, column("column_end")?)
+    }
+
+    /// Run one tensor-parallel shard: `input` times the shard's column slice of `weight`.
+    /// Feeding every shard's output to [`Self::reintegrate_shards`] gives `input.matmul(weight)`.
+    pub fn shard_matmul(&self, subgraph_id: Uuid, input: &Tensor, weight: &Tensor) -> Result<Tensor, WingbeatError> {
+        input.matmul(&self.shard_weight(subgraph_id, weight)?)
+    }
+
+    /// Combine tensor-parallel shard outputs into one tensor per layer, in layer order.
+    /// Shards of the same layer are concatenated along their last dimension rather than
+    /// treated as consecutive results.
+    pub fn reintegrate_shards(&self, shard_outputs: HashMap<Uuid, Tensor>) -> Result<Vec<Tensor>, WingbeatError> {
+        let mut sorted_shards: Vec<((usize, usize), Tensor)> = Vec::new();
+        
+        for (subgraph_id, output) in shard_outputs {
+            let is_shard = self.subgraph_metadata.get(&subgraph_id)
+                .is_some_and(|metadata| metadata.contains_key("shard_index"));
+            if let (true, Some(sort_key)) = (is_shard, self.sort_key_for_subgraph(subgraph_id)) {
+                sorted_shards.push((sort_key, output));
             }
         }
-        None
+        
+        sorted_shards.sort_by_key(|(index, _)| *index);
+        
+        let mut layer_outputs = Vec::new();
+        let mut start = 0;
+        while start < sorted_shards.len() {
+            let layer_index = sorted_shards[start].0.0;
+            let end = start + sorted_shards[start..].iter()
+                .take_while(|((index, _), _)| *index == layer_index)
+                .count();
+            
+            let parts: Vec<Tensor> = sorted_shards[start..end].iter()
+                .map(|(_, tensor)| tensor.clone())
+                .collect();
+            let last_dim = parts[0].shape.len().saturating_sub(1);
+            layer_outputs.push(Tensor::concat(&parts, last_dim)?);
+            start = end;
+        }
+        
+        Ok(layer_outputs)
+    }
+
+    /// Position of a subgraph in the model: (layer index, head/chunk index within the layer)
+    fn sort_key_for_subgraph(&self, subgraph_id: Uuid) -> Option<(usize, usize)> {
+        let metadata = self.subgraph_metadata.get(&subgraph_id)?;
+        let layer_id: Uuid = metadata.get("layer_id")?.parse().ok()?;
+        let layer_index = self.model_layers.iter().position(|l| l.id == layer_id)?;
+        
+        // Default to 0 for layers that were not split into heads or chunks
+        let sub_index = metadata.get("head_index")
+            .or_else(|| metadata.get("chunk_index"))
+            .or_else(|| metadata.get("shard_index"))
+            .or_else(|| metadata.get("shard_count")) // Aggregation runs after every shard
+            .and_then(|index| index.parse().ok())
+            .unwrap_or(0);
+        
+        Some((layer_index, sub_index))
     }
 }
 
-#[derive(Debug, Clone)]
+impl Default for ModelDecomposer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DecompositionStrategy {
     LayerWise,      // Each layer is a separate subgraph
     AttentionHeads, // Split attention layers into multiple heads
-    TokenWise,      // Split processing by tokens/chunks
+    TokenWise { chunks: usize }, // Split processing into contiguous token chunks
+    TensorParallel { shards: usize }, // Split weight columns across subgraphs
+}
+
+/// Range `[start, end)` owned by `part` when `len` items are split `parts` ways
+fn split_range(len: usize, part: usize, parts: usize) -> (usize, usize) {
+    let base = len / parts;
+    let remainder = len % parts;
+    let start = part * base + part.min(remainder);
+    let end = start + base + usize::from(part < remainder);
+    (start, end)
 } 
\ No newline at end of file
@@ -1,10 +1,20 @@
 use crate::core::subgraph::{Subgraph, ComputeNode, Operation, NodeState};
 use crate::swarm::tornado::{TornadoSwarm, Vec3};
-use std::sync::Arc;
+use crate::rng::WingbeatRng;
+use crate::error::WingbeatError;
+use rand::Rng;
+use futures::future::join_all;
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 use uuid::Uuid;
-use colored::*;
-use std::collections::HashMap;
+use tracing::{debug, info, instrument, warn};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::time::Duration;
+
+/// Simulation time advanced by each step of `PromptProcessor::process_until`
+const PROCESS_UNTIL_DELTA: f32 = 0.05;
 
 /// Represents a prompt being processed through the swarm
 #[derive(Debug, Clone)]
@@ -13,9 +23,19 @@ pub struct SwarmPrompt {
     pub content: String,
     pub origin: Vec3,
     pub status: PromptStatus,
+    pub priority: Priority,
     pub fragments: Vec<PromptFragment>,
 }
 
+/// Scheduling priority of a prompt; higher priorities are distributed first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
 #[derive(Debug, Clone)]
 pub enum PromptStatus {
     Sent,
@@ -23,6 +43,7 @@ pub enum PromptStatus {
     Processing,
     Assembling,
     Complete,
+    Cancelled,
 }
 
 #[derive(Debug, Clone)]
@@ -37,21 +58,69 @@ pub struct PromptFragment {
 pub struct PromptProcessor {
     pub swarm: Arc<TornadoSwarm>,
     pub active_prompts: Arc<RwLock<HashMap<Uuid, SwarmPrompt>>>,
+    rng: Mutex<WingbeatRng>,
+    // Prompts waiting for distribution, ordered by priority and then arrival
+    queue: Mutex<BinaryHeap<(Priority, Reverse<u64>, Uuid)>>,
+    next_sequence: AtomicU64,
 }
 
 impl PromptProcessor {
     pub fn new(swarm: Arc<TornadoSwarm>) -> Self {
+        Self::with_rng(swarm, WingbeatRng::from_entropy())
+    }
+
+    /// Create a processor whose fragmentation and subgraphs are reproducible
+    pub fn with_rng(swarm: Arc<TornadoSwarm>, rng: WingbeatRng) -> Self {
         Self {
             swarm,
             active_prompts: Arc::new(RwLock::new(HashMap::new())),
+            rng: Mutex::new(rng),
+            queue: Mutex::new(BinaryHeap::new()),
+            next_sequence: AtomicU64::new(0),
         }
     }
 
-    /// Send a prompt into the swarm
-    pub async fn send_prompt(&self, prompt: &str) -> Uuid {
-        let prompt_id = Uuid::new_v4();
+    /// Send a prompt into the swarm at the default priority
+    pub async fn send_prompt(&self, prompt: &str) -> Result<Uuid, WingbeatError> {
+        self.send_prompt_with_priority(prompt, Priority::default()).await
+    }
+
+    /// Queue a prompt and distribute everything queued, highest priority first. If any of
+    /// them fails to distribute the error is returned and this prompt is dropped as well.
+    #[instrument(name = "send_prompt", skip_all, fields(prompt_id, ?priority))]
+    pub async fn send_prompt_with_priority(&self, prompt: &str, priority: Priority) -> Result<Uuid, WingbeatError> {
+        let prompt_id = self.queue_prompt(prompt, priority).await;
+        tracing::Span::current().record("prompt_id", tracing::field::display(prompt_id));
         
-        println!("{}", format!("📤 Sending prompt into the swarm: '{}'", prompt).bright_green());
+        if let Err(e) = self.dispatch_queued().await {
+            // The caller never learns the id on failure, so don't leave the prompt behind
+            self.cancel(prompt_id).await;
+            self.active_prompts.write().await.remove(&prompt_id);
+            return Err(e);
+        }
+        Ok(prompt_id)
+    }
+
+    /// Fragment and store a prompt without distributing it yet; it is sent into the swarm
+    /// by the next `dispatch_queued` (or `process_step`) in priority order
+    pub async fn queue_prompt(&self, prompt: &str, priority: Priority) -> Uuid {
+        let prompt_id = self.rng.lock().unwrap().gen_uuid();
+        
+        // Nothing to distribute: record an already completed, empty prompt
+        if prompt.trim().is_empty() {
+            debug!(prompt_id = %prompt_id, "empty prompt, completing without the swarm");
+            self.active_prompts.write().await.insert(prompt_id, SwarmPrompt {
+                id: prompt_id,
+                content: String::new(),
+                origin: Vec3::new(0.0, 0.0, 0.0),
+                status: PromptStatus::Complete,
+                priority,
+                fragments: Vec::new(),
+            });
+            return prompt_id;
+        }
+        
+        info!(prompt_id = %prompt_id, prompt, ?priority, "sending prompt into the swarm");
         
         // Create prompt and fragment it
         let swarm_prompt = SwarmPrompt {
@@ -59,52 +128,77 @@ impl PromptProcessor {
             content: prompt.to_string(),
             origin: Vec3::new(0.0, 0.0, 0.0),
             status: PromptStatus::Sent,
+            priority,
             fragments: self.fragment_prompt(prompt, prompt_id),
         };
         
         // Store active prompt
-        self.active_prompts.write().await.insert(prompt_id, swarm_prompt.clone());
-        
-        // Distribute fragments to tornadoes
-        self.distribute_fragments(swarm_prompt).await;
+        self.active_prompts.write().await.insert(prompt_id, swarm_prompt);
         
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        self.queue.lock().unwrap().push((priority, Reverse(sequence), prompt_id));
         prompt_id
     }
 
+    /// Distribute every queued prompt, highest priority first and in arrival order within a
+    /// priority, returning their ids in the order they were sent. A prompt that cannot be
+    /// distributed is dropped and its error returned; prompts behind it stay queued.
+    pub async fn dispatch_queued(&self) -> Result<Vec<Uuid>, WingbeatError> {
+        let mut dispatched = Vec::new();
+        
+        loop {
+            let next = self.queue.lock().unwrap().pop();
+            let Some((_, _, prompt_id)) = next else {
+                break;
+            };
+            let prompt = self.active_prompts.read().await.get(&prompt_id).cloned();
+            let Some(prompt) = prompt.filter(|p| !matches!(p.status, PromptStatus::Cancelled)) else {
+                continue;
+            };
+            
+            if let Err(e) = self.distribute_fragments(prompt).await {
+                self.active_prompts.write().await.remove(&prompt_id);
+                return Err(e);
+            }
+            dispatched.push(prompt_id);
+        }
+        
+        Ok(dispatched)
+    }
+
     /// Fragment a prompt into smaller pieces
     fn fragment_prompt(&self, prompt: &str, _prompt_id: Uuid) -> Vec<PromptFragment> {
         let words: Vec<&str> = prompt.split_whitespace().collect();
         let mut fragments = Vec::new();
+        let mut rng = self.rng.lock().unwrap();
         
         // Create fragments of varying sizes (like irregular lego pieces)
         let mut i = 0;
         while i < words.len() {
-            let fragment_size = rand::random::<usize>() % 3 + 1; // 1-3 words per fragment
+            let fragment_size = rng.gen_range(1..=3); // 1-3 words per fragment
             let end = (i + fragment_size).min(words.len());
             
             let fragment_content = words[i..end].join(" ");
             
             fragments.push(PromptFragment {
-                id: Uuid::new_v4(),
+                id: rng.gen_uuid(),
                 content: fragment_content,
-                subgraph_id: Uuid::new_v4(),
+                subgraph_id: rng.gen_uuid(),
                 processed: false,
             });
             
             i = end;
         }
         
-        println!("{}", format!("🧩 Fragmented prompt into {} pieces", fragments.len()).yellow());
+        debug!(fragment_count = fragments.len(), "fragmented prompt");
         fragments
     }
 
     /// Distribute prompt fragments across the tornado swarm
-    async fn distribute_fragments(&self, prompt: SwarmPrompt) {
-        let tornadoes = self.swarm.tornadoes.read().await;
-        
-        if tornadoes.is_empty() {
+    #[instrument(skip_all, fields(prompt_id = %prompt.id, fragment_count = prompt.fragments.len()))]
+    async fn distribute_fragments(&self, prompt: SwarmPrompt) -> Result<(), WingbeatError> {
+        if self.swarm.tornadoes.read().await.is_empty() {
             // Spawn tornadoes if none exist
-            drop(tornadoes);
             for i in 0..3 {
                 let pos = Vec3::new(
                     (i as f32) * 10.0,
@@ -116,67 +210,194 @@ impl PromptProcessor {
         }
         
         let tornadoes = self.swarm.tornadoes.read().await;
+        if tornadoes.is_empty() {
+            return Err(WingbeatError::NoTornadoes);
+        }
         
-        // Create subgraphs for each fragment and sweep them into tornadoes
-        for (i, fragment) in prompt.fragments.iter().enumerate() {
-            let subgraph = Subgraph::new();
-            
-            // Add compute node to process this fragment
-            let node = ComputeNode {
-                id: Uuid::new_v4(),
-                operation: Operation::Process(fragment.content.clone()),
-                state: NodeState::Idle,
-                metadata: HashMap::new(),
-            };
-            
-            subgraph.graph.write().await.add_node(node);
-            
-            // Select a tornado to sweep this subgraph
-            let tornado_idx = i % tornadoes.len();
-            let tornado = &tornadoes[tornado_idx];
+        // Seed the subgraphs in fragment order so a seeded processor stays deterministic
+        let subgraphs: Vec<Subgraph> = {
+            let mut rng = self.rng.lock().unwrap();
+            prompt.fragments.iter().map(|_| Subgraph::new_seeded(&mut rng)).collect()
+        };
+        
+        // Build and sweep every fragment's subgraph concurrently
+        let sweeps = prompt.fragments.iter().zip(subgraphs).enumerate().map(|(i, (fragment, mut subgraph))| {
+            // The subgraph takes the fragment's id so the prompt can find it again
+            subgraph.id = fragment.subgraph_id;
+            let tornado = &tornadoes[i % tornadoes.len()];
             
-            tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
-        }
+            async move {
+                // Add compute node to process this fragment, tagged with its place in the prompt
+                let node = ComputeNode {
+                    id: Uuid::new_v4(),
+                    operation: Operation::Process(fragment.content.clone()),
+                    state: NodeState::Idle,
+                    metadata: HashMap::from([("fragment_index".to_string(), i.to_string())]),
+                };
+                
+                subgraph.graph.write().await.add_node(node);
+                tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+            }
+        });
+        join_all(sweeps).await;
         
         // Update prompt status
-        self.active_prompts.write().await.get_mut(&prompt.id)
-            .map(|p| p.status = PromptStatus::InWhirlwind);
+        if let Some(p) = self.active_prompts.write().await.get_mut(&prompt.id) {
+            p.status = PromptStatus::InWhirlwind;
+        }
         
-        println!("{}", "🌪️  Fragments swept up into the whirlwind!".bright_cyan());
+        info!("fragments swept up into the whirlwind");
+        Ok(())
     }
 
     /// Process the swarm for one time step
     pub async fn process_step(&self, delta_time: f32) {
+        if let Err(e) = self.dispatch_queued().await {
+            warn!(error = %e, "failed to distribute queued prompt");
+        }
+        
         // Simulate swarm dynamics
         self.swarm.simulate_step(delta_time).await;
         
         // Check for completed processing
         let tornadoes = self.swarm.tornadoes.read().await;
+        let mut spun = HashSet::new();
         
         for tornado in tornadoes.iter() {
             tornado.spin().await;
+            // Subgraphs merged away by the spin count as spun through the one that absorbed them
+            for (id, subgraph) in tornado.subgraphs.read().await.iter() {
+                spun.insert(*id);
+                spun.extend(subgraph.read().await.provenance.iter().copied());
+            }
+        }
+        drop(tornadoes);
+        
+        // A fragment is processed once its subgraph has gone round a tornado; a prompt whose
+        // fragments are all processed is ready to assemble
+        let mut prompts = self.active_prompts.write().await;
+        for prompt in prompts.values_mut().filter(|p| matches!(p.status, PromptStatus::InWhirlwind)) {
+            for fragment in prompt.fragments.iter_mut() {
+                fragment.processed |= spun.contains(&fragment.subgraph_id);
+            }
+            if prompt.fragments.iter().all(|f| f.processed) {
+                prompt.status = PromptStatus::Processing;
+            }
         }
     }
 
-    /// Collect results from the swarm
-    pub async fn collect_results(&self, prompt_id: Uuid) -> Option<String> {
-        let mut prompts = self.active_prompts.write().await;
+    /// Step the swarm until the prompt is ready to assemble, then collect its result.
+    /// Returns `None` if the prompt is unknown or does not finish within `timeout`.
+    pub async fn process_until(&self, id: Uuid, timeout: Duration) -> Option<String> {
+        let ready = tokio::time::timeout(timeout, async {
+            loop {
+                match self.active_prompts.read().await.get(&id).map(|p| p.status.clone()) {
+                    Some(PromptStatus::Processing | PromptStatus::Complete) => return true,
+                    None | Some(PromptStatus::Cancelled) => return false,
+                    Some(_) => {}
+                }
+                self.process_step(PROCESS_UNTIL_DELTA).await;
+                tokio::task::yield_now().await;
+            }
+        })
+        .await;
         
-        if let Some(prompt) = prompts.get_mut(&prompt_id) {
+        match ready {
+            Ok(true) => self.collect_results(id).await,
+            Ok(false) => None,
+            Err(_) => {
+                debug!(prompt_id = %id, ?timeout, "prompt did not finish before the timeout");
+                None
+            }
+        }
+    }
+
+    /// Cancel a prompt: release its subgraphs from whichever tornadoes hold them and mark it
+    /// `Cancelled`, so it is never dispatched or collected. Returns false if the prompt is
+    /// unknown or already cancelled.
+    pub async fn cancel(&self, id: Uuid) -> bool {
+        let fragments = {
+            let mut prompts = self.active_prompts.write().await;
+            match prompts.get_mut(&id) {
+                Some(prompt) if !matches!(prompt.status, PromptStatus::Cancelled) => {
+                    prompt.status = PromptStatus::Cancelled;
+                    prompt.fragments.clone()
+                }
+                _ => return false,
+            }
+        };
+        
+        let tornadoes = self.swarm.tornadoes.read().await;
+        let mut released = 0;
+        for fragment in &fragments {
+            for tornado in tornadoes.iter() {
+                if tornado.release_by_id(fragment.subgraph_id).await.is_some() {
+                    released += 1;
+                }
+            }
+        }
+        
+        info!(prompt_id = %id, released, "prompt cancelled");
+        true
+    }
+
+    /// Collect results from the swarm. The active prompts are only locked to claim the prompt
+    /// and to record its completion, so several prompts can be assembled at once.
+    pub async fn collect_results(&self, prompt_id: Uuid) -> Option<String> {
+        let prompt = {
+            let mut prompts = self.active_prompts.write().await;
+            let prompt = prompts.get_mut(&prompt_id)
+                .filter(|prompt| !matches!(prompt.status, PromptStatus::Cancelled))?;
             prompt.status = PromptStatus::Assembling;
-            
-            println!("{}", "📥 Collecting results from the swarm...".bright_magenta());
-            
-            // Simulate result assembly
-            let processed_content = prompt.content.to_uppercase();
-            
+            prompt.clone()
+        };
+        
+        debug!(prompt_id = %prompt_id, "collecting results from the swarm");
+        let processed_content = self.assemble(&prompt).await;
+        
+        if let Some(prompt) = self.active_prompts.write().await.get_mut(&prompt_id) {
             prompt.status = PromptStatus::Complete;
-            
-            println!("{}", format!("✅ Results assembled: {}", processed_content).bright_green());
-            
-            Some(processed_content)
-        } else {
-            None
         }
+        
+        info!(prompt_id = %prompt_id, result = %processed_content, "results assembled");
+        Some(processed_content)
+    }
+
+    /// Run each fragment through the subgraph carrying it and join the pieces in fragment
+    /// order. Fragments whose subgraph has left the swarm pass through unchanged.
+    async fn assemble(&self, prompt: &SwarmPrompt) -> String {
+        if prompt.fragments.is_empty() {
+            return prompt.content.to_uppercase();
+        }
+        
+        // Look the subgraphs up first so no swarm lock is held while they execute
+        let mut carriers = Vec::with_capacity(prompt.fragments.len());
+        {
+            let tornadoes = self.swarm.tornadoes.read().await;
+            for fragment in &prompt.fragments {
+                let mut carrier = None;
+                for tornado in tornadoes.iter() {
+                    if let Some(subgraph) = tornado.subgraphs.read().await.get(&fragment.subgraph_id) {
+                        carrier = Some(Arc::clone(subgraph));
+                        break;
+                    }
+                }
+                carriers.push(carrier);
+            }
+        }
+        
+        let mut pieces = Vec::with_capacity(prompt.fragments.len());
+        for (fragment, carrier) in prompt.fragments.iter().zip(carriers) {
+            let piece = match carrier {
+                Some(subgraph) => subgraph.read().await.execute(&fragment.content).await.unwrap_or_else(|e| {
+                    warn!(fragment_id = %fragment.id, error = %e, "fragment subgraph failed, passing it through");
+                    fragment.content.clone()
+                }),
+                None => fragment.content.clone(),
+            };
+            pieces.push(piece);
+        }
+        
+        pieces.join(" ").to_uppercase()
     }
-} 
\ No newline at end of file
+}
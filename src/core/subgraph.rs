@@ -1,8 +1,12 @@
-use std::collections::HashMap;
+use crate::rng::WingbeatRng;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
-use petgraph::graph::Graph;
-use std::sync::Arc;
+use petgraph::algo::toposort;
+use petgraph::graph::{Graph, NodeIndex};
+use petgraph::Direction;
+use std::sync::{Arc, OnceLock};
 use tokio::sync::RwLock;
 
 /// Represents a single computation node in a subgraph
@@ -11,9 +15,15 @@ pub struct ComputeNode {
     pub id: Uuid,
     pub operation: Operation,
     pub state: NodeState,
+    #[serde(serialize_with = "serialize_sorted")]
     pub metadata: HashMap<String, String>,
 }
 
+/// Serialize a map with its keys in order so equal maps always produce the same bytes
+fn serialize_sorted<S: serde::Serializer>(map: &HashMap<String, String>, serializer: S) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<std::collections::BTreeMap<_, _>>().serialize(serializer)
+}
+
 /// Types of operations a node can perform
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Operation {
@@ -25,6 +35,26 @@ pub enum Operation {
     Aggregate,
 }
 
+impl Operation {
+    /// Apply this operation to a string input
+    pub fn apply(&self, input: &str) -> String {
+        match self {
+            Operation::Transform(_) => input.to_uppercase(),
+            Operation::Split => input.split_whitespace().collect::<Vec<_>>().join("\n"),
+            Operation::Merge | Operation::Aggregate => input.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            Operation::Process(_) => input.to_string(),
+            Operation::Filter(word) => input.split_whitespace()
+                .filter(|w| w != word)
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
 /// State of a computation node
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NodeState {
@@ -35,24 +65,125 @@ pub enum NodeState {
     Complete,
 }
 
+/// Identifier for a subgraph
+pub type SubgraphId = Uuid;
+
+/// The model component a subgraph computes
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SubgraphType {
+    Embedding,
+    Attention,
+    FeedForward,
+    Output,
+    Custom(String),
+}
+
+/// Rules deciding which subgraph types may connect and how strength affects the score
+#[derive(Debug, Clone)]
+pub struct CompatibilityRules {
+    pub adjacency: HashSet<(SubgraphType, SubgraphType)>, // Unordered type pairs that may connect
+    pub strength_tolerance: f32,
+}
+
+impl CompatibilityRules {
+    /// Rules with no allowed type pairs
+    pub fn new(strength_tolerance: f32) -> Self {
+        Self {
+            adjacency: HashSet::new(),
+            strength_tolerance,
+        }
+    }
+
+    /// Allow subgraphs of types `a` and `b` to connect (in either direction)
+    pub fn allow(mut self, a: SubgraphType, b: SubgraphType) -> Self {
+        self.adjacency.insert((a, b));
+        self
+    }
+
+    /// Whether two types may connect; custom subgraphs connect to anything
+    pub fn allows(&self, a: &SubgraphType, b: &SubgraphType) -> bool {
+        if matches!(a, SubgraphType::Custom(_)) || matches!(b, SubgraphType::Custom(_)) {
+            return true;
+        }
+        self.adjacency.contains(&(a.clone(), b.clone())) || self.adjacency.contains(&(b.clone(), a.clone()))
+    }
+}
+
+impl Default for CompatibilityRules {
+    /// Follows the transformer dataflow; attention heads may also combine with each other
+    fn default() -> Self {
+        Self::new(0.3)
+            .allow(SubgraphType::Embedding, SubgraphType::Attention)
+            .allow(SubgraphType::Attention, SubgraphType::Attention)
+            .allow(SubgraphType::Attention, SubgraphType::FeedForward)
+            .allow(SubgraphType::FeedForward, SubgraphType::Output)
+    }
+}
+
+fn default_rules() -> &'static CompatibilityRules {
+    static RULES: OnceLock<CompatibilityRules> = OnceLock::new();
+    RULES.get_or_init(CompatibilityRules::default)
+}
+
+/// Serializable snapshot of a subgraph, including its graph topology
+#[derive(Serialize, Deserialize)]
+struct SubgraphSnapshot {
+    id: SubgraphId,
+    graph: Graph<ComputeNode, f32>,
+    parent: Option<Uuid>,
+    children: Vec<Uuid>,
+    tornado_strength: f32,
+    subgraph_type: SubgraphType,
+    #[serde(default)]
+    provenance: Vec<SubgraphId>,
+}
+
 /// A subgraph that can split, merge, and reorganize
 #[derive(Debug)]
 pub struct Subgraph {
-    pub id: Uuid,
+    pub id: SubgraphId,
     pub graph: Arc<RwLock<Graph<ComputeNode, f32>>>,
     pub parent: Option<Uuid>,
     pub children: Vec<Uuid>,
     pub tornado_strength: f32, // How strongly it's caught in the whirlwind
+    pub subgraph_type: SubgraphType,
+    pub provenance: Vec<SubgraphId>, // Ids of subgraphs merged into this one
 }
 
 impl Subgraph {
     pub fn new() -> Self {
+        Self::with_type(SubgraphType::Custom("generic".to_string()))
+    }
+
+    /// Create a subgraph whose id and strength come from a seedable generator
+    pub fn new_seeded(rng: &mut WingbeatRng) -> Self {
+        Self::with_type_seeded(SubgraphType::Custom("generic".to_string()), rng)
+    }
+
+    /// Create a subgraph with a caller-chosen id, e.g. for stable fixtures. Its strength is
+    /// derived from the id, so the same id always gives the same subgraph.
+    pub fn with_id(id: Uuid) -> Self {
+        let (high, low) = id.as_u64_pair();
+        let mut subgraph = Self::new_seeded(&mut WingbeatRng::seed_from_u64(high ^ low));
+        subgraph.id = id;
+        subgraph
+    }
+
+    /// Create a subgraph that computes a specific model component
+    pub fn with_type(subgraph_type: SubgraphType) -> Self {
+        Self::with_type_seeded(subgraph_type, &mut WingbeatRng::from_entropy())
+    }
+
+    /// Seeded variant of [`Subgraph::with_type`]
+    pub fn with_type_seeded(subgraph_type: SubgraphType, rng: &mut WingbeatRng) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: rng.gen_uuid(),
             graph: Arc::new(RwLock::new(Graph::new())),
             parent: None,
             children: Vec::new(),
-            tornado_strength: rand::random::<f32>(),
+            tornado_strength: rng.gen::<f32>(),
+            subgraph_type,
+            provenance: Vec::new(),
         }
     }
 
@@ -61,7 +192,7 @@ impl Subgraph {
         let mut splits = Vec::new();
         
         for _ in 0..num_splits {
-            let mut child = Subgraph::new();
+            let mut child = Subgraph::with_type(self.subgraph_type.clone());
             child.parent = Some(self.id);
             self.children.push(child.id);
             splits.push(child);
@@ -70,15 +201,53 @@ impl Subgraph {
         splits
     }
 
-    /// Merge with another subgraph
+    /// Merge with another subgraph, carrying over its nodes, edges and children. Every sink of
+    /// this graph gets an edge to every source of the other, chaining the two computations.
     pub async fn merge(&mut self, other: Subgraph) -> Result<(), String> {
-        let other_graph = other.graph.read().await;
-        let mut self_graph = self.graph.write().await;
+        if Arc::ptr_eq(&self.graph, &other.graph) {
+            return Err(format!("Cannot merge subgraph {} with itself", other.id));
+        }
+        
+        // Lock the two graphs in ascending id order, matching the order used for subgraph locks
+        let (mut self_graph, other_graph) = if self.id < other.id {
+            let self_graph = self.graph.write().await;
+            (self_graph, other.graph.read().await)
+        } else {
+            let other_graph = other.graph.read().await;
+            (self.graph.write().await, other_graph)
+        };
         
-        // Merge the graphs
-        for node in other_graph.node_weights() {
-            self_graph.add_node(node.clone());
+        // The merged graph runs this subgraph's nodes first and feeds its results into the other's
+        // sources, so it yields one output rather than one per original subgraph
+        let sinks: Vec<NodeIndex> = self_graph.node_indices()
+            .filter(|&i| self_graph.neighbors_directed(i, Direction::Outgoing).next().is_none())
+            .collect();
+        
+        // Re-add nodes, remembering where each one landed
+        let mut index_map = HashMap::new();
+        for index in other_graph.node_indices() {
+            let new_index = self_graph.add_node(other_graph[index].clone());
+            index_map.insert(index, new_index);
+        }
+        
+        for edge in other_graph.raw_edges() {
+            self_graph.add_edge(index_map[&edge.source()], index_map[&edge.target()], edge.weight);
+        }
+        for source in other_graph.node_indices() {
+            if other_graph.neighbors_directed(source, Direction::Incoming).next().is_none() {
+                for &sink in &sinks {
+                    self_graph.add_edge(sink, index_map[&source], 1.0);
+                }
+            }
+        }
+        
+        for child in &other.children {
+            if !self.children.contains(child) {
+                self.children.push(*child);
+            }
         }
+        self.provenance.push(other.id);
+        self.provenance.extend(other.provenance.iter().copied());
         
         // Update tornado strength as average
         self.tornado_strength = (self.tornado_strength + other.tornado_strength) / 2.0;
@@ -86,10 +255,133 @@ impl Subgraph {
         Ok(())
     }
 
+    /// Run a single node's operation on `input`, moving it Idle -> Processing -> Complete
+    pub async fn run_node(&self, id: Uuid, input: &str) -> Result<String, String> {
+        let mut graph = self.graph.write().await;
+        let index = graph.node_indices()
+            .find(|&i| graph[i].id == id)
+            .ok_or_else(|| format!("Node {} not found in subgraph {}", id, self.id))?;
+        
+        let node = &mut graph[index];
+        node.state = NodeState::Processing;
+        let output = node.operation.apply(input);
+        node.state = NodeState::Complete;
+        
+        Ok(output)
+    }
+
+    /// Run every node in dependency order, feeding outputs along edges. Sources receive
+    /// `input`; a node with several predecessors (and the final result, when there are
+    /// several sinks) joins their outputs with spaces.
+    pub async fn execute(&self, input: &str) -> Result<String, String> {
+        let (order, predecessors, sinks) = {
+            let graph = self.graph.read().await;
+            let order: Vec<(NodeIndex, Uuid)> = toposort(&*graph, None)
+                .map_err(|cycle| format!("Subgraph {} has a cycle at node {}", self.id, graph[cycle.node_id()].id))?
+                .into_iter()
+                .map(|i| (i, graph[i].id))
+                .collect();
+            
+            let mut predecessors = HashMap::new();
+            for &(index, _) in &order {
+                let mut incoming: Vec<NodeIndex> = graph.neighbors_directed(index, Direction::Incoming).collect();
+                incoming.sort();
+                incoming.dedup();
+                predecessors.insert(index, incoming);
+            }
+            
+            let sinks: Vec<NodeIndex> = order.iter()
+                .map(|&(i, _)| i)
+                .filter(|&i| graph.neighbors_directed(i, Direction::Outgoing).next().is_none())
+                .collect();
+            
+            (order, predecessors, sinks)
+        };
+        
+        if order.is_empty() {
+            return Ok(input.to_string());
+        }
+        
+        let mut outputs: HashMap<NodeIndex, String> = HashMap::new();
+        for (index, id) in order {
+            let node_input = if predecessors[&index].is_empty() {
+                input.to_string()
+            } else {
+                predecessors[&index].iter()
+                    .map(|p| outputs[p].as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            
+            let output = self.run_node(id, &node_input).await?;
+            outputs.insert(index, output);
+        }
+        
+        Ok(sinks.iter().map(|s| outputs[s].as_str()).collect::<Vec<_>>().join(" "))
+    }
+
+    /// Serialize the subgraph, including its nodes and edges, to JSON
+    pub async fn to_json(&self) -> Result<String, String> {
+        let snapshot = SubgraphSnapshot {
+            id: self.id,
+            graph: self.graph.read().await.clone(),
+            parent: self.parent,
+            children: self.children.clone(),
+            tornado_strength: self.tornado_strength,
+            subgraph_type: self.subgraph_type.clone(),
+            provenance: self.provenance.clone(),
+        };
+        
+        serde_json::to_string(&snapshot).map_err(|e| e.to_string())
+    }
+
+    /// Rebuild a subgraph from JSON produced by [`Subgraph::to_json`]
+    pub fn from_json(json: &str) -> Result<Subgraph, String> {
+        let snapshot: SubgraphSnapshot = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        
+        Ok(Self {
+            id: snapshot.id,
+            graph: Arc::new(RwLock::new(snapshot.graph)),
+            parent: snapshot.parent,
+            children: snapshot.children,
+            tornado_strength: snapshot.tornado_strength,
+            subgraph_type: snapshot.subgraph_type,
+            provenance: snapshot.provenance,
+        })
+    }
+
+    /// Score in `[0, 1]` for how well this subgraph fits with another under the default rules
+    pub fn compatibility(&self, other: &Subgraph) -> f32 {
+        self.compatibility_with(other, default_rules())
+    }
+
+    /// Score in `[0, 1]`: zero for disallowed type pairs, otherwise falling off linearly
+    /// with the tornado strength difference until it reaches the tolerance
+    pub fn compatibility_with(&self, other: &Subgraph, rules: &CompatibilityRules) -> f32 {
+        if !rules.allows(&self.subgraph_type, &other.subgraph_type) {
+            return 0.0;
+        }
+        
+        let diff = (self.tornado_strength - other.tornado_strength).abs();
+        (1.0 - diff / rules.strength_tolerance).max(0.0)
+    }
+
     /// Check if this subgraph can connect with another (like legos)
     pub fn can_connect_with(&self, other: &Subgraph) -> bool {
-        // Subgraphs can connect if their tornado strengths are compatible
-        (self.tornado_strength - other.tornado_strength).abs() < 0.3
+        self.can_connect_with_threshold(other, default_rules().strength_tolerance)
+    }
+
+    /// Like [`Subgraph::can_connect_with`], but the tornado strengths only need to be closer
+    /// than `threshold` instead of the default rules' tolerance
+    pub fn can_connect_with_threshold(&self, other: &Subgraph, threshold: f32) -> bool {
+        default_rules().allows(&self.subgraph_type, &other.subgraph_type)
+            && (self.tornado_strength - other.tornado_strength).abs() < threshold
+    }
+}
+
+impl Default for Subgraph {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -101,6 +393,8 @@ impl Clone for Subgraph {
             parent: self.parent,
             children: self.children.clone(),
             tornado_strength: self.tornado_strength,
+            subgraph_type: self.subgraph_type.clone(),
+            provenance: self.provenance.clone(),
         }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,35 @@
+use thiserror::Error;
+
+/// Errors produced by Wingbeat operations
+#[derive(Debug, Error)]
+pub enum WingbeatError {
+    #[error("Shape mismatch: expected {expected:?}, got {got:?}")]
+    ShapeMismatch { expected: Vec<usize>, got: Vec<usize> },
+
+    #[error("Invalid shape: {0}")]
+    InvalidShape(String),
+
+    #[error("Parameter not found: {0}")]
+    ParameterNotFound(String),
+
+    #[error("Tokenize error: {0}")]
+    TokenizeError(String),
+
+    #[error("No tornadoes available in the swarm")]
+    NoTornadoes,
+
+    #[error("Token id {id} is out of range for a vocabulary of {vocab_size}")]
+    TokenOutOfRange { id: u32, vocab_size: usize },
+
+    #[error("Token id {0} is not a non-negative integer")]
+    InvalidTokenId(f32),
+
+    #[error("Input is empty")]
+    EmptyInput,
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
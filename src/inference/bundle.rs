@@ -0,0 +1,59 @@
+use crate::error::WingbeatError;
+use crate::inference::{InferenceEngine, ModelWeights, TokenizerKind, TransformerConfig};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+const CONFIG_FILE: &str = "config.json";
+const WEIGHTS_FILE: &str = "weights.safetensors";
+const TOKENIZER_FILE: &str = "tokenizer.json";
+
+/// Everything needed to rebuild an [`InferenceEngine`], saved together as a directory of
+/// `config.json`, `weights.safetensors` and `tokenizer.json`
+#[derive(Debug, Clone)]
+pub struct ModelBundle {
+    pub config: TransformerConfig,
+    pub weights: ModelWeights,
+    pub tokenizer: TokenizerKind,
+}
+
+impl ModelBundle {
+    pub fn new(config: TransformerConfig, weights: ModelWeights, tokenizer: TokenizerKind) -> Self {
+        Self { config, weights, tokenizer }
+    }
+
+    /// Write the bundle into `path`, creating the directory if needed
+    pub fn save_dir(&self, path: impl AsRef<Path>) -> Result<(), WingbeatError> {
+        let dir = path.as_ref();
+        fs::create_dir_all(dir)?;
+        
+        fs::write(dir.join(CONFIG_FILE), to_json(&self.config)?)?;
+        fs::write(dir.join(TOKENIZER_FILE), to_json(&self.tokenizer)?)?;
+        self.weights.save(&dir.join(WEIGHTS_FILE).to_string_lossy())
+    }
+
+    /// Read a bundle written by [`ModelBundle::save_dir`]
+    pub fn load_dir(path: impl AsRef<Path>) -> Result<Self, WingbeatError> {
+        let dir = path.as_ref();
+        let config = from_json(&fs::read_to_string(dir.join(CONFIG_FILE))?)?;
+        let tokenizer = from_json(&fs::read_to_string(dir.join(TOKENIZER_FILE))?)?;
+        
+        let mut weights = ModelWeights::new();
+        weights.load(&dir.join(WEIGHTS_FILE).to_string_lossy())?;
+        
+        Ok(Self { config, weights, tokenizer })
+    }
+
+    /// Build a ready-to-run engine from the bundle
+    pub fn into_engine(self) -> InferenceEngine {
+        InferenceEngine::from_parts(&self.config, self.weights, Arc::from(self.tokenizer.into_tokenizer()))
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, WingbeatError> {
+    serde_json::to_string_pretty(value).map_err(|e| WingbeatError::Serialization(e.to_string()))
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, WingbeatError> {
+    serde_json::from_str(json).map_err(|e| WingbeatError::Serialization(e.to_string()))
+}
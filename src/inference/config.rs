@@ -0,0 +1,125 @@
+use serde::{Serialize, Deserialize};
+
+/// Layer norm epsilon used when a config doesn't set one
+pub const DEFAULT_NORM_EPS: f32 = 1e-5;
+
+/// Activation applied between the two feedforward projections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activation {
+    Relu,
+    Gelu,
+}
+
+/// Architecture description for a transformer model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransformerConfig {
+    pub num_layers: usize,
+    pub hidden_size: usize,
+    pub vocab_size: usize,
+    /// Attention heads, each attending over `hidden_size / num_heads` features
+    pub num_heads: usize,
+    pub intermediate_size: usize,
+    pub activation: Activation,
+    /// Added to the variance in the layer norms ahead of attention and feedforward
+    pub norm_eps: f32,
+    pub max_seq_len: usize,
+    /// Reuse the embedding table as the output projection instead of allocating `output.weight`
+    pub tie_embeddings: bool,
+    /// Allocate zero-initialized bias vectors for the attention and feedforward projections
+    #[serde(default)]
+    pub use_bias: bool,
+}
+
+impl TransformerConfig {
+    /// Create a config with the given dimensions, a single attention head (so any hidden
+    /// size works) and conventional defaults for the rest
+    pub fn new(num_layers: usize, hidden_size: usize, vocab_size: usize) -> Self {
+        Self {
+            num_layers,
+            hidden_size,
+            vocab_size,
+            num_heads: 1,
+            intermediate_size: hidden_size * 4,
+            activation: Activation::Relu,
+            norm_eps: DEFAULT_NORM_EPS,
+            max_seq_len: 1024,
+            tie_embeddings: true,
+            use_bias: false,
+        }
+    }
+
+    pub fn with_num_heads(mut self, num_heads: usize) -> Self {
+        self.num_heads = num_heads;
+        self
+    }
+
+    pub fn with_intermediate_size(mut self, intermediate_size: usize) -> Self {
+        self.intermediate_size = intermediate_size;
+        self
+    }
+
+    pub fn with_activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    pub fn with_norm_eps(mut self, norm_eps: f32) -> Self {
+        self.norm_eps = norm_eps;
+        self
+    }
+
+    pub fn with_max_seq_len(mut self, max_seq_len: usize) -> Self {
+        self.max_seq_len = max_seq_len;
+        self
+    }
+
+    pub fn with_tie_embeddings(mut self, tie_embeddings: bool) -> Self {
+        self.tie_embeddings = tie_embeddings;
+        self
+    }
+
+    pub fn with_use_bias(mut self, use_bias: bool) -> Self {
+        self.use_bias = use_bias;
+        self
+    }
+}
+
+impl Default for TransformerConfig {
+    fn default() -> Self {
+        Self::new(12, 768, 51200).with_num_heads(12)
+    }
+}
+
+/// Options for [`InferenceEngine::infer_batch`](crate::InferenceEngine::infer_batch)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchConfig {
+    /// Token id used to pad shorter sequences up to the longest one in the batch
+    pub pad_token_id: u32,
+    /// Most sequences run in a single forward pass; larger inputs are split into several
+    pub max_batch_size: usize,
+}
+
+impl BatchConfig {
+    pub fn new() -> Self {
+        Self {
+            pad_token_id: 0,
+            max_batch_size: 16,
+        }
+    }
+
+    pub fn with_pad_token_id(mut self, pad_token_id: u32) -> Self {
+        self.pad_token_id = pad_token_id;
+        self
+    }
+
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
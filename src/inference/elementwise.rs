@@ -0,0 +1,107 @@
+//! Element-wise kernels behind `relu`, `add` and `multiply` and their in-place variants.
+//! With the `simd` feature they process 8 lanes at a time through `wide`, finishing the
+//! remainder one scalar at a time; without it they are plain loops that LLVM vectorizes.
+
+#[cfg(feature = "simd")]
+use wide::f32x8;
+
+#[cfg(feature = "simd")]
+const LANES: usize = 8;
+
+#[cfg(feature = "simd")]
+fn load(chunk: &[f32]) -> f32x8 {
+    f32x8::new(chunk.try_into().unwrap())
+}
+
+/// Apply `lanes` to each full 8-lane chunk of `a` and `b` and `scalar` to the remainder
+#[cfg(feature = "simd")]
+fn zip_map(a: &[f32], b: &[f32], lanes: impl Fn(f32x8, f32x8) -> f32x8, scalar: impl Fn(f32, f32) -> f32) -> Vec<f32> {
+    let mut out = Vec::with_capacity(a.len());
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let (a_rest, b_rest) = (a_chunks.remainder(), b_chunks.remainder());
+    for (x, y) in a_chunks.zip(b_chunks) {
+        out.extend_from_slice(&lanes(load(x), load(y)).to_array());
+    }
+    out.extend(a_rest.iter().zip(b_rest).map(|(&x, &y)| scalar(x, y)));
+    out
+}
+
+/// Every element clamped to be at least zero. `fast_max` picks zero for NaN, like `f32::max`.
+#[cfg(feature = "simd")]
+pub(crate) fn relu(data: &[f32]) -> Vec<f32> {
+    let mut out = Vec::with_capacity(data.len());
+    let chunks = data.chunks_exact(LANES);
+    let rest = chunks.remainder();
+    for chunk in chunks {
+        out.extend_from_slice(&load(chunk).fast_max(f32x8::ZERO).to_array());
+    }
+    out.extend(rest.iter().map(|x| x.max(0.0)));
+    out
+}
+
+#[cfg(not(feature = "simd"))]
+pub(crate) fn relu(data: &[f32]) -> Vec<f32> {
+    data.iter().map(|x| x.max(0.0)).collect()
+}
+
+#[cfg(feature = "simd")]
+pub(crate) fn relu_in_place(data: &mut [f32]) {
+    let mut chunks = data.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        let clamped = load(chunk).fast_max(f32x8::ZERO);
+        chunk.copy_from_slice(&clamped.to_array());
+    }
+    for x in chunks.into_remainder() {
+        *x = x.max(0.0);
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+pub(crate) fn relu_in_place(data: &mut [f32]) {
+    for x in data.iter_mut() {
+        *x = x.max(0.0);
+    }
+}
+
+/// Element-wise sum; the slices must have the same length
+#[cfg(feature = "simd")]
+pub(crate) fn add(a: &[f32], b: &[f32]) -> Vec<f32> {
+    zip_map(a, b, |x, y| x + y, |x, y| x + y)
+}
+
+#[cfg(not(feature = "simd"))]
+pub(crate) fn add(a: &[f32], b: &[f32]) -> Vec<f32> {
+    a.iter().zip(b).map(|(x, y)| x + y).collect()
+}
+
+#[cfg(feature = "simd")]
+pub(crate) fn add_in_place(a: &mut [f32], b: &[f32]) {
+    let mut a_chunks = a.chunks_exact_mut(LANES);
+    let b_chunks = b.chunks_exact(LANES);
+    let b_rest = b_chunks.remainder();
+    for (x, y) in (&mut a_chunks).zip(b_chunks) {
+        let sum = load(x) + load(y);
+        x.copy_from_slice(&sum.to_array());
+    }
+    for (x, y) in a_chunks.into_remainder().iter_mut().zip(b_rest) {
+        *x += y;
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+pub(crate) fn add_in_place(a: &mut [f32], b: &[f32]) {
+    for (x, y) in a.iter_mut().zip(b) {
+        *x += y;
+    }
+}
+
+/// Element-wise product; the slices must have the same length
+#[cfg(feature = "simd")]
+pub(crate) fn mul(a: &[f32], b: &[f32]) -> Vec<f32> {
+    zip_map(a, b, |x, y| x * y, |x, y| x * y)
+}
+
+#[cfg(not(feature = "simd"))]
+pub(crate) fn mul(a: &[f32], b: &[f32]) -> Vec<f32> {
+    a.iter().zip(b).map(|(x, y)| x * y).collect()
+}
@@ -1,6 +1,11 @@
-use crate::inference::{tensor_ops::Tensor, model_weights::ModelWeights};
+use crate::inference::{tensor_ops::{Tensor, TensorOps}, model_weights::{ModelWeights, ModelParameter}, config::{Activation, TransformerConfig, DEFAULT_NORM_EPS}};
 use crate::computation::model_decomposer::LayerType;
+use crate::error::WingbeatError;
+use crate::rng::WingbeatRng;
 use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use std::sync::{Mutex, OnceLock, RwLock};
+use tracing::warn;
 use uuid::Uuid;
 
 /// Input/output context for layer operations
@@ -8,6 +13,7 @@ use uuid::Uuid;
 pub struct LayerContext {
     pub input: Tensor,
     pub output: Option<Tensor>,
+    pub attention_mask: Option<Tensor>, // 1.0 = attend, 0.0 = masked
     pub metadata: HashMap<String, String>,
 }
 
@@ -19,12 +25,114 @@ pub struct LayerResult {
 }
 
 /// Trait for layer operations that can be executed
-pub trait LayerOperation {
-    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, String>;
+pub trait LayerOperation: std::fmt::Debug + Send + Sync {
+    /// Run the layer on a borrowed input, without a mask or training-mode metadata
+    fn forward(&self, input: &Tensor, weights: &ModelWeights) -> Result<Tensor, WingbeatError>;
+    
+    /// Run the layer with a full context and report metadata about the run.
+    /// The default forwards `context.input` and returns no metadata.
+    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, WingbeatError> {
+        Ok(LayerResult {
+            output: self.forward(&context.input, weights)?,
+            metadata: HashMap::new(),
+        })
+    }
+    
     fn layer_type(&self) -> LayerType;
     fn layer_id(&self) -> Uuid;
 }
 
+/// Add -inf to attention scores wherever the mask is zero.
+/// The mask is either `[seq_k]`/`[1, seq_k]` (broadcast over queries) or `[seq_q, seq_k]`.
+pub fn apply_attention_mask(scores: &Tensor, mask: &Tensor) -> Result<Tensor, WingbeatError> {
+    if scores.shape.len() != 2 {
+        return Err(WingbeatError::InvalidShape("Attention scores must be 2D".to_string()));
+    }
+    
+    let (rows, cols) = (scores.shape[0], scores.shape[1]);
+    let broadcast = mask.data.len() == cols && (mask.shape.len() == 1 || mask.shape[0] == 1);
+    
+    if !broadcast && mask.shape != scores.shape {
+        return Err(WingbeatError::ShapeMismatch {
+            expected: scores.shape.clone(),
+            got: mask.shape.clone(),
+        });
+    }
+    
+    let mut data = scores.data.clone();
+    for i in 0..rows {
+        for j in 0..cols {
+            let keep = if broadcast { mask.data[j] } else { mask.data[i * cols + j] };
+            if keep == 0.0 {
+                data[i * cols + j] = f32::NEG_INFINITY;
+            }
+        }
+    }
+    
+    Ok(Tensor::new(scores.shape.clone(), data))
+}
+
+/// Fetch the weight `name`, checking it has the `expected` shape
+fn fetch_weight<'a>(weights: &'a ModelWeights, name: &str, expected: &[usize]) -> Result<&'a ModelParameter, WingbeatError> {
+    let param = weights.get_parameter(name)
+        .ok_or_else(|| WingbeatError::ParameterNotFound(name.to_string()))?;
+    param.assert_shape(expected)?;
+    Ok(param)
+}
+
+/// Add the bias parameter `name` to every row of `output`, if the model has one.
+/// Biases are `[features]` vectors matching the last dimension of `output`.
+pub fn add_bias(output: &mut Tensor, weights: &ModelWeights, name: &str) -> Result<(), WingbeatError> {
+    let Some(bias) = weights.get_parameter(name) else {
+        return Ok(());
+    };
+    
+    let features = output.shape.last().copied().unwrap_or(0);
+    if bias.tensor.size() != features {
+        return Err(WingbeatError::ShapeMismatch {
+            expected: vec![features],
+            got: bias.tensor.shape.clone(),
+        });
+    }
+    
+    for row in output.data.chunks_mut(features) {
+        for (value, b) in row.iter_mut().zip(&bias.tensor.data) {
+            *value += b;
+        }
+    }
+    Ok(())
+}
+
+/// Normalize each row of `input` to zero mean and unit variance, then scale it by the
+/// `[features]` parameter `name`, if the model has one. `eps` keeps constant rows finite.
+fn layer_norm(input: &Tensor, weights: &ModelWeights, name: &str, eps: f32) -> Result<Tensor, WingbeatError> {
+    let Some(scale) = weights.get_parameter(name) else {
+        return Ok(input.clone());
+    };
+    
+    let features = input.shape.last().copied().unwrap_or(0);
+    if scale.tensor.size() != features {
+        return Err(WingbeatError::ShapeMismatch {
+            expected: vec![features],
+            got: scale.tensor.shape.clone(),
+        });
+    }
+    
+    let mut output = input.clone();
+    if features == 0 {
+        return Ok(output);
+    }
+    for row in output.data.chunks_mut(features) {
+        let mean = row.iter().sum::<f32>() / features as f32;
+        let variance = row.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / features as f32;
+        let inv_std = 1.0 / (variance + eps).sqrt();
+        for (value, s) in row.iter_mut().zip(&scale.tensor.data) {
+            *value = (*value - mean) * inv_std * s;
+        }
+    }
+    Ok(output)
+}
+
 /// Embedding layer operation
 #[derive(Debug)]
 pub struct EmbeddingLayer {
@@ -34,32 +142,33 @@ pub struct EmbeddingLayer {
 }
 
 impl LayerOperation for EmbeddingLayer {
-    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, String> {
+    fn forward(&self, input: &Tensor, weights: &ModelWeights) -> Result<Tensor, WingbeatError> {
         // Get embedding weights
-        let embedding_weight = weights.get_parameter("embedding.weight")
-            .ok_or("Embedding weights not found")?;
+        let embedding_weight = fetch_weight(weights, "embedding.weight", &[self.vocab_size, self.hidden_size])?;
         
-        // Simple embedding lookup (in practice, this would be more sophisticated)
-        let input_data = &context.input.data;
-        let mut output_data = Vec::new();
+        // Look up one row per token id; ids that aren't whole numbers or lie past the
+        // vocabulary are an error, not a truncation or wrap-around
+        let input_data = &input.data;
+        let mut output_data = Vec::with_capacity(input_data.len() * self.hidden_size);
         
         for &token_id in input_data {
-            let token_idx = token_id as usize % self.vocab_size;
-            let start_idx = token_idx * self.hidden_size;
-            let end_idx = start_idx + self.hidden_size;
-            
-            if end_idx <= embedding_weight.tensor.data.len() {
-                output_data.extend_from_slice(&embedding_weight.tensor.data[start_idx..end_idx]);
-            } else {
-                // Pad with zeros if out of bounds
-                output_data.extend(vec![0.0; self.hidden_size]);
+            if !token_id.is_finite() || token_id < 0.0 || token_id.fract() != 0.0 {
+                return Err(WingbeatError::InvalidTokenId(token_id));
+            }
+            let id = token_id as u32;
+            if id as usize >= self.vocab_size {
+                return Err(WingbeatError::TokenOutOfRange { id, vocab_size: self.vocab_size });
             }
+            
+            output_data.extend_from_slice(&embedding_weight.tensor.row(id as usize)?.data);
         }
         
-        let output = Tensor::new(vec![input_data.len(), self.hidden_size], output_data);
-        
+        Ok(Tensor::new(vec![input_data.len(), self.hidden_size], output_data))
+    }
+
+    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, WingbeatError> {
         Ok(LayerResult {
-            output,
+            output: self.forward(&context.input, weights)?,
             metadata: HashMap::from([
                 ("operation".to_string(), "embedding".to_string()),
                 ("vocab_size".to_string(), self.vocab_size.to_string()),
@@ -77,47 +186,101 @@ impl LayerOperation for EmbeddingLayer {
     }
 }
 
+/// Dropout applied during training; layers run it only when the context metadata has `train = "true"`
+#[derive(Debug)]
+pub struct Dropout {
+    pub p: f32,
+    rng: Mutex<WingbeatRng>,
+}
+
+impl Dropout {
+    pub fn new(p: f32, rng: WingbeatRng) -> Self {
+        Self { p, rng: Mutex::new(rng) }
+    }
+
+    /// Apply dropout to `tensor` if `context` is in training mode
+    pub fn apply(&self, tensor: &Tensor, context: &LayerContext) -> Tensor {
+        let train = context.metadata.get("train").is_some_and(|v| v == "true");
+        tensor.dropout(self.p, train, &mut self.rng.lock().unwrap())
+    }
+}
+
 /// Attention layer operation
 #[derive(Debug)]
 pub struct AttentionLayer {
     pub layer_id: Uuid,
     pub hidden_size: usize,
+    /// Must divide `hidden_size`; each head attends over its own slice of the projections
     pub num_heads: usize,
+    /// Epsilon for the `attention_norm.weight` layer norm applied to the input
+    pub norm_eps: f32,
+    pub dropout: Option<Dropout>, // Applied to the attention probabilities
 }
 
-impl LayerOperation for AttentionLayer {
-    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, String> {
-        let input = &context.input;
-        
+impl AttentionLayer {
+    /// Masking and dropout come from `context` when there is one
+    fn attend(&self, input: &Tensor, weights: &ModelWeights, context: Option<&LayerContext>) -> Result<Tensor, WingbeatError> {
         // Get attention weights
-        let query_weight = weights.get_parameter("attention.query.weight")
-            .ok_or("Query weights not found")?;
-        let key_weight = weights.get_parameter("attention.key.weight")
-            .ok_or("Key weights not found")?;
-        let value_weight = weights.get_parameter("attention.value.weight")
-            .ok_or("Value weights not found")?;
-        let output_weight = weights.get_parameter("attention.output.weight")
-            .ok_or("Output weights not found")?;
+        let projection = [self.hidden_size, self.hidden_size];
+        let query_weight = fetch_weight(weights, "attention.query.weight", &projection)?;
+        let key_weight = fetch_weight(weights, "attention.key.weight", &projection)?;
+        let value_weight = fetch_weight(weights, "attention.value.weight", &projection)?;
+        let output_weight = fetch_weight(weights, "attention.output.weight", &projection)?;
+        
+        if self.num_heads == 0 || !self.hidden_size.is_multiple_of(self.num_heads) {
+            return Err(WingbeatError::InvalidShape(format!(
+                "Hidden size {} does not split into {} attention heads", self.hidden_size, self.num_heads
+            )));
+        }
         
-        // Compute Q, K, V
-        let query = input.matmul(&query_weight.tensor)?;
-        let key = input.matmul(&key_weight.tensor)?;
-        let value = input.matmul(&value_weight.tensor)?;
+        // Compute Q, K, V from the normalized input
+        let input = layer_norm(input, weights, "attention_norm.weight", self.norm_eps)?;
+        let mut query = input.matmul(&query_weight.tensor)?;
+        let mut key = input.matmul(&key_weight.tensor)?;
+        let mut value = input.matmul(&value_weight.tensor)?;
+        add_bias(&mut query, weights, "attention.query.bias")?;
+        add_bias(&mut key, weights, "attention.key.bias")?;
+        add_bias(&mut value, weights, "attention.value.bias")?;
         
-        // Simple attention computation (simplified)
-        let attention_scores = query.matmul(&key.transpose())?;
-        let attention_probs = attention_scores.softmax();
-        let attention_output = attention_probs.matmul(&value)?;
+        // Each head attends over its own columns of Q, K and V; the heads are then concatenated
+        let head_size = self.hidden_size / self.num_heads;
+        let mut heads = Vec::with_capacity(self.num_heads);
+        for head in 0..self.num_heads {
+            let (start, end) = (head * head_size, (head + 1) * head_size);
+            let head_key = key.slice_dim(1, start, end)?;
+            let mut attention_scores = query.slice_dim(1, start, end)?.matmul(&head_key.transpose())?;
+            if let Some(mask) = context.and_then(|c| c.attention_mask.as_ref()) {
+                attention_scores = apply_attention_mask(&attention_scores, mask)?;
+            }
+            let mut attention_probs = attention_scores.softmax();
+            if let (Some(dropout), Some(context)) = (&self.dropout, context) {
+                attention_probs = dropout.apply(&attention_probs, context);
+            }
+            heads.push(attention_probs.matmul(&value.slice_dim(1, start, end)?)?);
+        }
+        let attention_output = Tensor::concat(&heads, 1)?;
         
         // Apply output projection
-        let output = attention_output.matmul(&output_weight.tensor)?;
+        let mut output = attention_output.matmul(&output_weight.tensor)?;
+        add_bias(&mut output, weights, "attention.output.bias")?;
         
+        Ok(output)
+    }
+}
+
+impl LayerOperation for AttentionLayer {
+    fn forward(&self, input: &Tensor, weights: &ModelWeights) -> Result<Tensor, WingbeatError> {
+        self.attend(input, weights, None)
+    }
+
+    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, WingbeatError> {
         Ok(LayerResult {
-            output,
+            output: self.attend(&context.input, weights, Some(&context))?,
             metadata: HashMap::from([
                 ("operation".to_string(), "attention".to_string()),
                 ("hidden_size".to_string(), self.hidden_size.to_string()),
                 ("num_heads".to_string(), self.num_heads.to_string()),
+                ("norm_eps".to_string(), self.norm_eps.to_string()),
             ]),
         })
     }
@@ -136,30 +299,44 @@ impl LayerOperation for AttentionLayer {
 pub struct FeedForwardLayer {
     pub layer_id: Uuid,
     pub hidden_size: usize,
+    pub activation: Activation,
+    /// Epsilon for the `ffn_norm.weight` layer norm applied to the input
+    pub norm_eps: f32,
 }
 
 impl LayerOperation for FeedForwardLayer {
-    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, String> {
-        let input = &context.input;
-        
+    fn forward(&self, input: &Tensor, weights: &ModelWeights) -> Result<Tensor, WingbeatError> {
         // Get feedforward weights
         let intermediate_weight = weights.get_parameter("ffn.intermediate.weight")
-            .ok_or("Intermediate weights not found")?;
-        let output_weight = weights.get_parameter("ffn.output.weight")
-            .ok_or("Output weights not found")?;
+            .ok_or_else(|| WingbeatError::ParameterNotFound("ffn.intermediate.weight".to_string()))?;
+        let intermediate_size = intermediate_weight.tensor.shape.get(1).copied().unwrap_or(0);
+        intermediate_weight.assert_shape(&[self.hidden_size, intermediate_size])?;
+        let output_weight = fetch_weight(weights, "ffn.output.weight", &[intermediate_size, self.hidden_size])?;
         
-        // Apply intermediate layer
-        let intermediate = input.matmul(&intermediate_weight.tensor)?;
-        let activated = intermediate.relu();
+        // Apply intermediate layer to the normalized input
+        let input = layer_norm(input, weights, "ffn_norm.weight", self.norm_eps)?;
+        let mut activated = input.matmul(&intermediate_weight.tensor)?;
+        add_bias(&mut activated, weights, "ffn.intermediate.bias")?;
+        match self.activation {
+            Activation::Relu => activated.relu_(),
+            Activation::Gelu => activated.gelu_(),
+        }
         
         // Apply output layer
-        let output = activated.matmul(&output_weight.tensor)?;
+        let mut output = activated.matmul(&output_weight.tensor)?;
+        add_bias(&mut output, weights, "ffn.output.bias")?;
         
+        Ok(output)
+    }
+
+    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, WingbeatError> {
         Ok(LayerResult {
-            output,
+            output: self.forward(&context.input, weights)?,
             metadata: HashMap::from([
                 ("operation".to_string(), "feedforward".to_string()),
                 ("hidden_size".to_string(), self.hidden_size.to_string()),
+                ("activation".to_string(), format!("{:?}", self.activation)),
+                ("norm_eps".to_string(), self.norm_eps.to_string()),
             ]),
         })
     }
@@ -179,25 +356,29 @@ pub struct OutputLayer {
     pub layer_id: Uuid,
     pub hidden_size: usize,
     pub vocab_size: usize,
+    /// Project with `embedding.weight` (tied) instead of a separate `output.weight`
+    pub tie_embeddings: bool,
 }
 
 impl LayerOperation for OutputLayer {
-    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, String> {
-        let input = &context.input;
-        
-        // Get output projection weights (using embedding weights as output projection)
-        let output_weight = weights.get_parameter("embedding.weight")
-            .ok_or("Output weights not found")?;
+    fn forward(&self, input: &Tensor, weights: &ModelWeights) -> Result<Tensor, WingbeatError> {
+        // Get output projection weights. When tied, the [vocab, hidden] embedding table
+        // doubles as the output projection; otherwise `output.weight` has the same layout.
+        let weight_name = if self.tie_embeddings { "embedding.weight" } else { "output.weight" };
+        let output_weight = fetch_weight(weights, weight_name, &[self.vocab_size, self.hidden_size])?;
         
         // Apply output projection
-        let logits = input.matmul(&output_weight.tensor.transpose())?;
-        
+        input.matmul(&output_weight.tensor.transpose())
+    }
+
+    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, WingbeatError> {
         Ok(LayerResult {
-            output: logits,
+            output: self.forward(&context.input, weights)?,
             metadata: HashMap::from([
                 ("operation".to_string(), "output".to_string()),
                 ("hidden_size".to_string(), self.hidden_size.to_string()),
                 ("vocab_size".to_string(), self.vocab_size.to_string()),
+                ("tie_embeddings".to_string(), self.tie_embeddings.to_string()),
             ]),
         })
     }
@@ -211,10 +392,94 @@ impl LayerOperation for OutputLayer {
     }
 }
 
+/// Builds a user-supplied layer from its id and the factory's size config
+pub type LayerConstructor = Box<dyn Fn(Uuid, &HashMap<String, usize>) -> Box<dyn LayerOperation> + Send + Sync>;
+
+/// Constructors registered for `LayerType::Custom` names
+fn custom_layers() -> &'static RwLock<HashMap<String, LayerConstructor>> {
+    static CUSTOM_LAYERS: OnceLock<RwLock<HashMap<String, LayerConstructor>>> = OnceLock::new();
+    CUSTOM_LAYERS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// How a [`PoolingLayer`] reduces the sequence dimension
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoolKind {
+    /// Average over all positions
+    #[default]
+    Mean,
+    /// Element-wise maximum over all positions
+    Max,
+    /// The first position's hidden state
+    Cls,
+}
+
+/// Pooling layer operation: reduces a `[seq, hidden]` tensor to `[1, hidden]`
+#[derive(Debug)]
+pub struct PoolingLayer {
+    pub layer_id: Uuid,
+    pub kind: PoolKind,
+}
+
+impl PoolingLayer {
+    pub fn new(layer_id: Uuid, kind: PoolKind) -> Self {
+        Self { layer_id, kind }
+    }
+}
+
+impl LayerOperation for PoolingLayer {
+    fn forward(&self, input: &Tensor, _weights: &ModelWeights) -> Result<Tensor, WingbeatError> {
+        if input.shape.len() != 2 || input.shape[0] == 0 {
+            return Err(WingbeatError::InvalidShape(format!(
+                "Pooling needs a non-empty [seq, hidden] tensor, got {:?}", input.shape
+            )));
+        }
+        
+        let hidden = input.shape[1];
+        let output = match self.kind {
+            PoolKind::Mean => input.mean_dim(0),
+            PoolKind::Max => {
+                let mut data = vec![f32::NEG_INFINITY; hidden];
+                for row in input.data.chunks(hidden) {
+                    for (best, &x) in data.iter_mut().zip(row) {
+                        *best = best.max(x);
+                    }
+                }
+                Tensor::new(vec![1, hidden], data)
+            },
+            PoolKind::Cls => input.slice_dim(0, 0, 1)?,
+        };
+        
+        Ok(output)
+    }
+
+    fn execute(&self, context: LayerContext, weights: &ModelWeights) -> Result<LayerResult, WingbeatError> {
+        Ok(LayerResult {
+            output: self.forward(&context.input, weights)?,
+            metadata: HashMap::from([
+                ("operation".to_string(), "pooling".to_string()),
+                ("kind".to_string(), format!("{:?}", self.kind)),
+            ]),
+        })
+    }
+
+    fn layer_type(&self) -> LayerType {
+        LayerType::Pooling
+    }
+
+    fn layer_id(&self) -> Uuid {
+        self.layer_id
+    }
+}
+
 /// Factory for creating layer operations
 pub struct LayerFactory;
 
 impl LayerFactory {
+    /// Register the constructor used for `LayerType::Custom(name)`, replacing any earlier one
+    pub fn register(name: &str, ctor: LayerConstructor) {
+        custom_layers().write().unwrap().insert(name.to_string(), ctor);
+    }
+
     pub fn create_layer(layer_type: LayerType, layer_id: Uuid, config: HashMap<String, usize>) -> Box<dyn LayerOperation> {
         match layer_type {
             LayerType::Embedding => {
@@ -225,22 +490,60 @@ impl LayerFactory {
             LayerType::Attention => {
                 let hidden_size = config.get("hidden_size").copied().unwrap_or(768);
                 let num_heads = config.get("num_heads").copied().unwrap_or(12);
-                Box::new(AttentionLayer { layer_id, hidden_size, num_heads })
+                Box::new(AttentionLayer { layer_id, hidden_size, num_heads, norm_eps: DEFAULT_NORM_EPS, dropout: None })
             },
             LayerType::FeedForward => {
                 let hidden_size = config.get("hidden_size").copied().unwrap_or(768);
-                Box::new(FeedForwardLayer { layer_id, hidden_size })
+                Box::new(FeedForwardLayer { layer_id, hidden_size, activation: Activation::Relu, norm_eps: DEFAULT_NORM_EPS })
             },
             LayerType::Output => {
                 let hidden_size = config.get("hidden_size").copied().unwrap_or(768);
                 let vocab_size = config.get("vocab_size").copied().unwrap_or(51200);
-                Box::new(OutputLayer { layer_id, hidden_size, vocab_size })
+                Box::new(OutputLayer { layer_id, hidden_size, vocab_size, tie_embeddings: true })
             },
-            LayerType::Custom(_) => {
-                // Placeholder for custom layers
+            LayerType::Pooling => Box::new(PoolingLayer::new(layer_id, PoolKind::default())),
+            LayerType::Custom(name) => {
+                if let Some(ctor) = custom_layers().read().unwrap().get(&name) {
+                    return ctor(layer_id, &config);
+                }
+                
+                // Unregistered custom layers fall back to a feedforward block
+                warn!(name = %name, "no constructor registered for custom layer, using feedforward");
                 let hidden_size = config.get("hidden_size").copied().unwrap_or(768);
-                Box::new(FeedForwardLayer { layer_id, hidden_size })
+                Box::new(FeedForwardLayer { layer_id, hidden_size, activation: Activation::Relu, norm_eps: DEFAULT_NORM_EPS })
             }
         }
     }
-} 
\ No newline at end of file
+
+    /// Create a layer whose dimensions and activation come from a [`TransformerConfig`]
+    pub fn from_config(layer_type: LayerType, layer_id: Uuid, cfg: &TransformerConfig) -> Box<dyn LayerOperation> {
+        match layer_type {
+            LayerType::Attention => Box::new(AttentionLayer {
+                layer_id,
+                hidden_size: cfg.hidden_size,
+                num_heads: cfg.num_heads,
+                norm_eps: cfg.norm_eps,
+                dropout: None,
+            }),
+            LayerType::FeedForward => Box::new(FeedForwardLayer {
+                layer_id,
+                hidden_size: cfg.hidden_size,
+                activation: cfg.activation,
+                norm_eps: cfg.norm_eps,
+            }),
+            LayerType::Output => Box::new(OutputLayer {
+                layer_id,
+                hidden_size: cfg.hidden_size,
+                vocab_size: cfg.vocab_size,
+                tie_embeddings: cfg.tie_embeddings,
+            }),
+            other => {
+                let config = HashMap::from([
+                    ("hidden_size".to_string(), cfg.hidden_size),
+                    ("vocab_size".to_string(), cfg.vocab_size),
+                ]);
+                Self::create_layer(other, layer_id, config)
+            }
+        }
+    }
+}
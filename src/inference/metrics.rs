@@ -0,0 +1,58 @@
+use crate::computation::model_decomposer::LayerType;
+use serde::{Serialize, Deserialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Counters updated by every forward pass of an [`InferenceEngine`](crate::InferenceEngine)
+#[derive(Debug, Default)]
+pub struct InferenceMetrics {
+    pub tokens_generated: AtomicU64,
+    pub forward_passes: AtomicU64,
+    pub forward_micros: AtomicU64,
+}
+
+/// Point-in-time copy of [`InferenceMetrics`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct InferenceMetricsSnapshot {
+    pub tokens_generated: u64,
+    pub forward_passes: u64,
+    pub avg_forward_ms: f64,
+}
+
+/// Execution time of one layer operation during a profiled forward pass
+#[derive(Debug, Clone)]
+pub struct LayerTiming {
+    pub layer_index: usize,
+    pub layer_type: LayerType,
+    pub duration: Duration,
+}
+
+impl InferenceMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one completed forward pass
+    pub fn record_forward(&self, tokens: usize, elapsed: Duration) {
+        self.tokens_generated.fetch_add(tokens as u64, Ordering::Relaxed);
+        self.forward_passes.fetch_add(1, Ordering::Relaxed);
+        self.forward_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Read all counters
+    pub fn snapshot(&self) -> InferenceMetricsSnapshot {
+        let forward_passes = self.forward_passes.load(Ordering::Relaxed);
+        let forward_micros = self.forward_micros.load(Ordering::Relaxed);
+        let avg_forward_ms = if forward_passes == 0 {
+            0.0
+        } else {
+            forward_micros as f64 / forward_passes as f64 / 1000.0
+        };
+        
+        InferenceMetricsSnapshot {
+            tokens_generated: self.tokens_generated.load(Ordering::Relaxed),
+            forward_passes,
+            avg_forward_ms,
+        }
+    }
+}
@@ -2,140 +2,432 @@ pub mod tensor_ops;
 pub mod model_weights;
 pub mod layer_ops;
 pub mod tokenizer;
+pub mod config;
+pub mod metrics;
+pub mod payload;
+pub mod similarity;
+pub mod bundle;
+mod elementwise;
 
-pub use tensor_ops::{Tensor, DataType, TensorOps};
+use crate::error::WingbeatError;
+use crate::rng::WingbeatRng;
+use crate::computation::model_decomposer::LayerType;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Instant;
+use uuid::Uuid;
+
+pub use tensor_ops::{Tensor, DataType, TensorOps, cross_entropy};
 pub use model_weights::{ModelWeights, ModelParameter};
-pub use layer_ops::{LayerOperation, LayerContext, LayerResult, LayerFactory};
-pub use tokenizer::{Tokenizer, Token, SimpleTokenizer, BPETokenizer, TokenizerFactory};
+pub use layer_ops::{LayerOperation, LayerContext, LayerResult, LayerFactory, LayerConstructor, PoolingLayer, PoolKind};
+pub use tokenizer::{Tokenizer, Token, SimpleTokenizer, BPETokenizer, TokenizerKind, TokenizerFactory, NormalizerConfig};
+pub use config::{TransformerConfig, Activation, BatchConfig};
+pub use metrics::{InferenceMetrics, InferenceMetricsSnapshot, LayerTiming};
+pub use payload::TensorPayload;
+pub use similarity::{cosine, NearestNeighbors};
+pub use bundle::ModelBundle;
+
+/// Callback invoked with a layer's output during a forward pass
+pub type ForwardHook = Box<dyn Fn(&Tensor) + Send + Sync>;
+
+/// Registered hooks are reference-counted so cloned engines can share them
+type SharedHook = Arc<dyn Fn(&Tensor) + Send + Sync>;
 
 /// Inference engine that coordinates all components
-#[derive(Debug)]
 pub struct InferenceEngine {
-    pub weights: ModelWeights,
-    pub tokenizer: Box<dyn Tokenizer>,
-    pub layer_operations: Vec<Box<dyn LayerOperation>>,
+    pub weights: Arc<ModelWeights>,
+    pub tokenizer: Arc<dyn Tokenizer>,
+    pub layer_operations: Vec<Arc<dyn LayerOperation>>,
+    pub config: Option<TransformerConfig>,
+    num_layers: usize,
+    pub metrics: Arc<InferenceMetrics>,
+    hooks: HashMap<usize, Vec<SharedHook>>,
+}
+
+/// Cloning is cheap: the replica shares the weights, tokenizer, layer operations, hooks
+/// and metrics with the original. Initializing more layers on either one copies the
+/// weights first, so the other is never affected.
+impl Clone for InferenceEngine {
+    fn clone(&self) -> Self {
+        Self {
+            weights: Arc::clone(&self.weights),
+            tokenizer: Arc::clone(&self.tokenizer),
+            layer_operations: self.layer_operations.clone(),
+            config: self.config.clone(),
+            num_layers: self.num_layers,
+            metrics: Arc::clone(&self.metrics),
+            hooks: self.hooks.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for InferenceEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InferenceEngine")
+            .field("weights", &self.weights)
+            .field("tokenizer", &self.tokenizer)
+            .field("layer_operations", &self.layer_operations)
+            .field("config", &self.config)
+            .field("num_layers", &self.num_layers)
+            .field("metrics", &self.metrics)
+            .field("hooks", &self.hooks.values().map(Vec::len).sum::<usize>())
+            .finish()
+    }
 }
 
 impl InferenceEngine {
     pub fn new(tokenizer_type: &str) -> Self {
         Self {
-            weights: ModelWeights::new(),
-            tokenizer: TokenizerFactory::create_tokenizer(tokenizer_type),
+            weights: Arc::new(ModelWeights::new()),
+            tokenizer: Arc::from(TokenizerFactory::create_tokenizer(tokenizer_type)),
             layer_operations: Vec::new(),
+            config: None,
+            num_layers: 0,
+            metrics: Arc::new(InferenceMetrics::new()),
+            hooks: HashMap::new(),
+        }
+    }
+
+    /// Register a hook that observes the output of the layer at `layer_index`
+    pub fn register_hook(&mut self, layer_index: usize, hook: ForwardHook) {
+        self.hooks.entry(layer_index).or_default().push(Arc::from(hook));
+    }
+
+    /// Invoke the hooks registered for a layer
+    fn run_hooks(&self, layer_index: usize, output: &Tensor) {
+        if let Some(hooks) = self.hooks.get(&layer_index) {
+            for hook in hooks {
+                hook(output);
+            }
         }
     }
 
     /// Initialize a basic transformer model
     pub fn init_transformer(&mut self, num_layers: usize, hidden_size: usize, vocab_size: usize) {
-        use crate::computation::model_decomposer::LayerType;
-        use uuid::Uuid;
-        use std::collections::HashMap;
+        self.init_from_config(&TransformerConfig::new(num_layers, hidden_size, vocab_size));
+    }
+
+    /// Seeded variant of [`InferenceEngine::init_transformer`] for reproducible weights
+    pub fn init_transformer_seeded(
+        &mut self,
+        num_layers: usize,
+        hidden_size: usize,
+        vocab_size: usize,
+        rng: &mut WingbeatRng,
+    ) {
+        self.init_from_config_seeded(&TransformerConfig::new(num_layers, hidden_size, vocab_size), rng);
+    }
+
+    /// Initialize a transformer model described by `cfg`
+    pub fn init_from_config(&mut self, cfg: &TransformerConfig) {
+        self.init_from_config_seeded(cfg, &mut WingbeatRng::from_entropy());
+    }
+
+    /// Seeded variant of [`InferenceEngine::init_from_config`] for reproducible weights
+    pub fn init_from_config_seeded(&mut self, cfg: &TransformerConfig, rng: &mut WingbeatRng) {
+        let weights = Arc::make_mut(&mut self.weights);
 
         // Initialize weights for each layer
-        for layer_idx in 0..num_layers {
-            let layer_id = Uuid::new_v4();
-            self.weights.init_transformer_layer(layer_id, hidden_size, vocab_size);
-            
-            // Create layer operations
-            let config = HashMap::from([
-                ("hidden_size".to_string(), hidden_size),
-                ("vocab_size".to_string(), vocab_size),
-                ("num_heads".to_string(), 12),
-            ]);
+        let mut layer_ids = Vec::with_capacity(cfg.num_layers);
+        for _ in 0..cfg.num_layers {
+            let layer_id = rng.gen_uuid();
+            weights.init_layer_from_config(layer_id, cfg, rng);
+            layer_ids.push(layer_id);
+        }
+
+        // Initialize the output projection
+        let output_layer_id = rng.gen_uuid();
+        weights.init_output_from_config(output_layer_id, cfg, rng);
+        self.push_layers(cfg, &layer_ids, output_layer_id);
+    }
+
+    /// Build an engine around existing weights, e.g. loaded from a checkpoint. Layer
+    /// operations look their parameters up by name, so they get fresh layer ids.
+    pub fn from_parts(cfg: &TransformerConfig, weights: ModelWeights, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        let mut engine = Self::new("simple");
+        engine.weights = Arc::new(weights);
+        engine.tokenizer = tokenizer;
+        
+        let layer_ids: Vec<Uuid> = (0..cfg.num_layers).map(|_| Uuid::new_v4()).collect();
+        engine.push_layers(cfg, &layer_ids, Uuid::new_v4());
+        engine
+    }
 
+    /// Append the layer operations for a model described by `cfg`: an embedding, an
+    /// attention and feedforward block per entry of `layer_ids`, then the output layer
+    fn push_layers(&mut self, cfg: &TransformerConfig, layer_ids: &[Uuid], output_layer_id: Uuid) {
+        for (layer_idx, &layer_id) in layer_ids.iter().enumerate() {
             // Add embedding layer (only for first layer)
             if layer_idx == 0 {
-                let embedding_layer = LayerFactory::create_layer(
-                    LayerType::Embedding,
-                    layer_id,
-                    config.clone(),
-                );
-                self.layer_operations.push(embedding_layer);
+                self.layer_operations.push(LayerFactory::from_config(LayerType::Embedding, layer_id, cfg).into());
             }
 
-            // Add attention layer
-            let attention_layer = LayerFactory::create_layer(
-                LayerType::Attention,
-                layer_id,
-                config.clone(),
-            );
-            self.layer_operations.push(attention_layer);
-
-            // Add feedforward layer
-            let ffn_layer = LayerFactory::create_layer(
-                LayerType::FeedForward,
-                layer_id,
-                config.clone(),
-            );
-            self.layer_operations.push(ffn_layer);
+            // Add attention and feedforward layers
+            self.layer_operations.push(LayerFactory::from_config(LayerType::Attention, layer_id, cfg).into());
+            self.layer_operations.push(LayerFactory::from_config(LayerType::FeedForward, layer_id, cfg).into());
         }
 
         // Add output layer
-        let output_layer_id = Uuid::new_v4();
-        let output_config = HashMap::from([
-            ("hidden_size".to_string(), hidden_size),
-            ("vocab_size".to_string(), vocab_size),
-        ]);
-        let output_layer = LayerFactory::create_layer(
-            LayerType::Output,
-            output_layer_id,
-            output_config,
-        );
-        self.layer_operations.push(output_layer);
+        self.layer_operations.push(LayerFactory::from_config(LayerType::Output, output_layer_id, cfg).into());
+        self.num_layers += layer_ids.len();
+        self.config = Some(cfg.clone());
     }
 
     /// Run inference on a text input
-    pub fn infer(&self, text: &str) -> Result<String, String> {
-        // Tokenize input
-        let tokens = self.tokenizer.encode(text)?;
+    pub fn infer(&self, text: &str) -> Result<String, WingbeatError> {
+        let started = Instant::now();
         
-        // Convert tokens to tensor
-        let token_ids: Vec<f32> = tokens.iter().map(|t| t.id as f32).collect();
-        let mut input_tensor = Tensor::new(vec![1, token_ids.len()], token_ids);
+        // Tokenize input and run every layer
+        let output = self.run_layers(self.prepare_input(text)?, 0..self.layer_operations.len())?;
         
-        // Run through all layers
-        for layer_op in &self.layer_operations {
-            let context = LayerContext {
-                input: input_tensor.clone(),
-                output: None,
-                metadata: HashMap::new(),
-            };
-            
-            let result = layer_op.execute(context, &self.weights)?;
-            input_tensor = result.output;
+        let decoded = self.decode_output(&output)?;
+        self.metrics.record_forward(output.shape.first().copied().unwrap_or(0), started.elapsed());
+        Ok(decoded)
+    }
+
+    /// Run inference on several texts at once. Each batch of up to `config.max_batch_size`
+    /// texts is padded to its longest sequence and run through the layers as one `[B, S]`
+    /// input; an attention mask keeps every sequence to its own real tokens, so each result
+    /// matches what [`InferenceEngine::infer`] returns for that text alone.
+    pub fn infer_batch(&self, texts: &[&str], config: &BatchConfig) -> Result<Vec<String>, WingbeatError> {
+        let mut outputs = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(config.max_batch_size.max(1)) {
+            outputs.extend(self.infer_padded_batch(batch, config)?);
         }
+        Ok(outputs)
+    }
+
+    fn infer_padded_batch(&self, texts: &[&str], config: &BatchConfig) -> Result<Vec<String>, WingbeatError> {
+        let started = Instant::now();
+        let sequences = texts
+            .iter()
+            .map(|text| self.prepare_input(text).map(|input| input.data))
+            .collect::<Result<Vec<_>, _>>()?;
+        let batch = sequences.len();
+        let seq_len = sequences.iter().map(Vec::len).max().unwrap_or(0);
+        let total = batch * seq_len;
         
-        // Convert output back to tokens (simplified)
-        let output_tokens = self.tensor_to_tokens(&input_tensor)?;
+        // Pad every sequence to the same length
+        let mut ids = Vec::with_capacity(total);
+        for sequence in &sequences {
+            ids.extend_from_slice(sequence);
+            ids.resize(ids.len() + seq_len - sequence.len(), config.pad_token_id as f32);
+        }
         
-        // Decode tokens back to text
-        self.tokenizer.decode(&output_tokens)
+        // Block-diagonal mask: each position attends only to its own sequence's real tokens
+        let mut mask = vec![0.0; total * total];
+        for (b, sequence) in sequences.iter().enumerate() {
+            for query in b * seq_len..(b + 1) * seq_len {
+                let row = query * total + b * seq_len;
+                mask[row..row + sequence.len()].fill(1.0);
+            }
+        }
+        let mask = Tensor::new(vec![total, total], mask);
+        
+        let mut output = Tensor::new(vec![batch, seq_len], ids);
+        for layer_index in 0..self.layer_operations.len() {
+            output = self.run_layer_masked(layer_index, &output, Some(&mask))?;
+        }
+        
+        // Split the rows back per input, dropping the padding
+        let decoded = sequences
+            .iter()
+            .enumerate()
+            .map(|(b, sequence)| {
+                let start = b * seq_len;
+                self.decode_output(&output.slice_dim(0, start, start + sequence.len())?)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        let real_tokens = sequences.iter().map(Vec::len).sum();
+        self.metrics.record_forward(real_tokens, started.elapsed());
+        Ok(decoded)
     }
 
-    /// Convert output tensor back to tokens (simplified)
-    fn tensor_to_tokens(&self, tensor: &Tensor) -> Result<Vec<Token>, String> {
-        // This is a simplified conversion - in practice you'd do proper sampling
-        let mut tokens = Vec::new();
+    /// Encode `text` to a `[1, hidden]` vector: the mean-pooled hidden state from the
+    /// embedding and transformer blocks, stopping before the output projection
+    pub fn embed(&self, text: &str) -> Result<Tensor, WingbeatError> {
+        let started = Instant::now();
+        let hidden_layers = self.layer_operations
+            .iter()
+            .position(|op| matches!(op.layer_type(), LayerType::Output))
+            .unwrap_or(self.layer_operations.len());
         
-        for (i, &logit) in tensor.data.iter().enumerate() {
-            let token_id = logit as u32 % self.tokenizer.vocab_size() as u32;
-            tokens.push(Token {
-                id: token_id,
-                text: format!("token_{}", token_id),
-                start: i,
-                end: i + 1,
+        let hidden = self.run_layers(self.prepare_input(text)?, 0..hidden_layers)?;
+        let pooled = PoolingLayer::new(Uuid::nil(), PoolKind::Mean).forward(&hidden, &self.weights)?;
+        
+        self.metrics.record_forward(0, started.elapsed());
+        Ok(pooled)
+    }
+
+    /// Run inference like [`InferenceEngine::infer`], also timing each layer operation
+    pub fn infer_profiled(&self, text: &str) -> Result<(String, Vec<LayerTiming>), WingbeatError> {
+        let started = Instant::now();
+        let mut timings = Vec::with_capacity(self.layer_operations.len());
+        
+        let mut output = self.prepare_input(text)?;
+        for (layer_index, layer_op) in self.layer_operations.iter().enumerate() {
+            let layer_started = Instant::now();
+            output = self.run_layer(layer_index, &output)?;
+            timings.push(LayerTiming {
+                layer_index,
+                layer_type: layer_op.layer_type(),
+                duration: layer_started.elapsed(),
             });
         }
         
+        let decoded = self.decode_output(&output)?;
+        self.metrics.record_forward(output.shape.first().copied().unwrap_or(0), started.elapsed());
+        Ok((decoded, timings))
+    }
+
+    /// Perplexity of `text` under the model: `exp` of the mean next-token cross-entropy,
+    /// using a single teacher-forced forward pass
+    pub fn perplexity(&self, text: &str) -> Result<f32, WingbeatError> {
+        if text.trim().is_empty() {
+            return Err(WingbeatError::EmptyInput);
+        }
+        let tokens = self.tokenizer.encode(text)?;
+        if tokens.len() < 2 {
+            return Err(WingbeatError::InvalidShape(
+                "Perplexity needs at least two tokens".to_string(),
+            ));
+        }
+        
+        let logits = self.run_layers(self.tokens_to_input(&tokens)?, 0..self.layer_operations.len())?;
+        
+        // Position i predicts token i + 1
+        let predictions = logits.slice_dim(0, 0, tokens.len() - 1)?;
+        let targets: Vec<u32> = tokens[1..].iter().map(|t| t.id).collect();
+        
+        Ok(cross_entropy(&predictions, &targets)?.exp())
+    }
+
+    /// Tokenize `text` into the `[1, seq]` tensor of token ids fed to the first layer.
+    /// Empty and whitespace-only text is rejected with `EmptyInput`.
+    pub fn prepare_input(&self, text: &str) -> Result<Tensor, WingbeatError> {
+        if text.trim().is_empty() {
+            return Err(WingbeatError::EmptyInput);
+        }
+        let tokens = self.tokenizer.encode(text)?;
+        self.tokens_to_input(&tokens)
+    }
+
+    fn tokens_to_input(&self, tokens: &[Token]) -> Result<Tensor, WingbeatError> {
+        if tokens.is_empty() {
+            return Err(WingbeatError::EmptyInput);
+        }
+        if let Some(cfg) = &self.config {
+            if tokens.len() > cfg.max_seq_len {
+                return Err(WingbeatError::InvalidShape(format!(
+                    "Sequence length {} exceeds max_seq_len {}",
+                    tokens.len(),
+                    cfg.max_seq_len
+                )));
+            }
+        }
+        
+        let token_ids: Vec<f32> = tokens.iter().map(|t| t.id as f32).collect();
+        Ok(Tensor::new(vec![1, token_ids.len()], token_ids))
+    }
+
+    /// Run the layers in `layers` in order, feeding each one's output to the next
+    fn run_layers(&self, input: Tensor, layers: Range<usize>) -> Result<Tensor, WingbeatError> {
+        layers.into_iter().try_fold(input, |output, layer_index| self.run_layer(layer_index, &output))
+    }
+
+    /// Run the layer at `layer_index` on `input`, invoking its hooks
+    pub fn run_layer(&self, layer_index: usize, input: &Tensor) -> Result<Tensor, WingbeatError> {
+        self.run_layer_masked(layer_index, input, None)
+    }
+
+    fn run_layer_masked(
+        &self,
+        layer_index: usize,
+        input: &Tensor,
+        attention_mask: Option<&Tensor>,
+    ) -> Result<Tensor, WingbeatError> {
+        let layer_op = self.layer_operations.get(layer_index).ok_or_else(|| {
+            WingbeatError::InvalidShape(format!(
+                "Layer {} out of range for {} layers", layer_index, self.layer_operations.len()
+            ))
+        })?;
+        
+        // Only a mask needs the context; otherwise borrow the input instead of cloning it
+        let output = match attention_mask {
+            Some(mask) => {
+                let context = LayerContext {
+                    input: input.clone(),
+                    output: None,
+                    attention_mask: Some(mask.clone()),
+                    metadata: HashMap::new(),
+                };
+                layer_op.execute(context, &self.weights)?.output
+            },
+            None => layer_op.forward(input, &self.weights)?,
+        };
+        self.run_hooks(layer_index, &output);
+        Ok(output)
+    }
+
+    /// Decode the final layer's output back into text
+    pub fn decode_output(&self, output: &Tensor) -> Result<String, WingbeatError> {
+        // Convert output back to tokens (simplified)
+        let output_tokens = self.tensor_to_tokens(output)?;
+        self.tokenizer.decode(&output_tokens)
+    }
+
+    /// Greedy decoding: one token per position, the argmax of that position's logits.
+    /// Logits past the tokenizer's vocabulary are ignored.
+    fn tensor_to_tokens(&self, tensor: &Tensor) -> Result<Vec<Token>, WingbeatError> {
+        let width = tensor.shape.last().copied().unwrap_or(0);
+        let vocab = width.min(self.tokenizer.vocab_size());
+        if vocab == 0 {
+            return Err(WingbeatError::InvalidShape(format!(
+                "Cannot decode logits of shape {:?}", tensor.shape
+            )));
+        }
+        
+        let tokens = tensor.data
+            .chunks(width)
+            .enumerate()
+            .map(|(position, logits)| {
+                let token_id = logits[..vocab]
+                    .iter()
+                    .enumerate()
+                    .fold(0, |best, (i, &logit)| if logit > logits[best] { i } else { best }) as u32;
+                // `decode` looks the text up by id
+                Token {
+                    id: token_id,
+                    text: String::new(),
+                    start: position,
+                    end: position + 1,
+                }
+            })
+            .collect();
+        
         Ok(tokens)
     }
 
-    /// Get model statistics
+    /// Number of transformer layers (attention + feedforward blocks) in the model
+    pub fn num_layers(&self) -> usize {
+        self.num_layers
+    }
+
+    /// Get model statistics, including a `parameters.<group>` entry per parameter group
     pub fn get_stats(&self) -> HashMap<String, String> {
-        HashMap::from([
+        let mut stats = HashMap::from([
             ("total_parameters".to_string(), self.weights.parameter_count().to_string()),
-            ("num_layers".to_string(), (self.layer_operations.len() / 3).to_string()), // Rough estimate
+            ("num_layers".to_string(), self.num_layers.to_string()),
             ("vocab_size".to_string(), self.tokenizer.vocab_size().to_string()),
-        ])
+        ]);
+        
+        for (group, count) in self.weights.parameter_breakdown() {
+            stats.insert(format!("parameters.{}", group), count.to_string());
+        }
+        
+        stats
     }
 } 
\ No newline at end of file
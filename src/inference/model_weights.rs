@@ -1,20 +1,46 @@
 use crate::inference::tensor_ops::Tensor;
+use crate::inference::config::TransformerConfig;
+use crate::error::WingbeatError;
+use crate::rng::WingbeatRng;
+use safetensors::{serialize_to_file, Dtype, SafeTensors};
+use safetensors::tensor::TensorView;
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 
-/// Represents a model parameter/weight
+/// Represents a model parameter/weight.
+///
+/// Layout convention: projection weights are stored `[in_features, out_features]` and applied
+/// as `input.matmul(weight)`; the embedding and output tables are `[vocab, hidden]`, so the
+/// output projection multiplies by their transpose. Layers check these shapes with
+/// [`ModelParameter::assert_shape`] when they fetch their weights.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelParameter {
     pub id: Uuid,
     pub name: String,
-    pub tensor: Tensor,
+    pub tensor: Arc<Tensor>,
     pub requires_grad: bool,
     pub layer_id: Uuid,
 }
 
+impl ModelParameter {
+    /// Error with `ShapeMismatch` unless this parameter's tensor has exactly the `expected` shape
+    pub fn assert_shape(&self, expected: &[usize]) -> Result<(), WingbeatError> {
+        if self.tensor.shape != expected {
+            return Err(WingbeatError::ShapeMismatch {
+                expected: expected.to_vec(),
+                got: self.tensor.shape.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Manages model weights and parameters
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ModelWeights {
     pub parameters: HashMap<String, ModelParameter>,
     pub layer_parameters: HashMap<Uuid, Vec<String>>,
@@ -33,7 +59,7 @@ impl ModelWeights {
         let param = ModelParameter {
             id: Uuid::new_v4(),
             name: name.clone(),
-            tensor,
+            tensor: Arc::new(tensor),
             requires_grad: true,
             layer_id,
         };
@@ -43,7 +69,7 @@ impl ModelWeights {
         // Track parameters by layer
         self.layer_parameters
             .entry(layer_id)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(name);
     }
 
@@ -52,6 +78,12 @@ impl ModelWeights {
         self.parameters.get(name)
     }
 
+    /// Hand out a reference-counted handle to a parameter's tensor without copying it,
+    /// e.g. to move into a task running on another tornado
+    pub fn share_parameter(&self, name: &str) -> Option<Arc<Tensor>> {
+        self.parameters.get(name).map(|param| Arc::clone(&param.tensor))
+    }
+
     /// Get all parameters for a layer
     pub fn get_layer_parameters(&self, layer_id: Uuid) -> Vec<&ModelParameter> {
         if let Some(param_names) = self.layer_parameters.get(&layer_id) {
@@ -65,48 +97,68 @@ impl ModelWeights {
 
     /// Initialize weights for a typical transformer layer
     pub fn init_transformer_layer(&mut self, layer_id: Uuid, hidden_size: usize, vocab_size: usize) {
+        self.init_transformer_layer_seeded(layer_id, hidden_size, vocab_size, &mut WingbeatRng::from_entropy());
+    }
+
+    /// Seeded variant of [`ModelWeights::init_transformer_layer`]
+    pub fn init_transformer_layer_seeded(
+        &mut self,
+        layer_id: Uuid,
+        hidden_size: usize,
+        vocab_size: usize,
+        rng: &mut WingbeatRng,
+    ) {
+        let cfg = TransformerConfig::new(1, hidden_size, vocab_size);
+        self.init_layer_from_config(layer_id, &cfg, rng);
+    }
+
+    /// Initialize weights for one transformer layer described by `cfg`
+    pub fn init_layer_from_config(&mut self, layer_id: Uuid, cfg: &TransformerConfig, rng: &mut WingbeatRng) {
+        let (hidden_size, vocab_size) = (cfg.hidden_size, cfg.vocab_size);
+        let intermediate_size = cfg.intermediate_size;
+
         // Embedding weights
         self.add_parameter(
             "embedding.weight".to_string(),
-            Tensor::random(vec![vocab_size, hidden_size]),
+            Tensor::random_seeded(vec![vocab_size, hidden_size], rng),
             layer_id,
         );
 
         // Attention weights
         self.add_parameter(
             "attention.query.weight".to_string(),
-            Tensor::random(vec![hidden_size, hidden_size]),
+            Tensor::random_seeded(vec![hidden_size, hidden_size], rng),
             layer_id,
         );
         
         self.add_parameter(
             "attention.key.weight".to_string(),
-            Tensor::random(vec![hidden_size, hidden_size]),
+            Tensor::random_seeded(vec![hidden_size, hidden_size], rng),
             layer_id,
         );
         
         self.add_parameter(
             "attention.value.weight".to_string(),
-            Tensor::random(vec![hidden_size, hidden_size]),
+            Tensor::random_seeded(vec![hidden_size, hidden_size], rng),
             layer_id,
         );
         
         self.add_parameter(
             "attention.output.weight".to_string(),
-            Tensor::random(vec![hidden_size, hidden_size]),
+            Tensor::random_seeded(vec![hidden_size, hidden_size], rng),
             layer_id,
         );
 
         // Feedforward weights
         self.add_parameter(
             "ffn.intermediate.weight".to_string(),
-            Tensor::random(vec![hidden_size, hidden_size * 4]),
+            Tensor::random_seeded(vec![hidden_size, intermediate_size], rng),
             layer_id,
         );
         
         self.add_parameter(
             "ffn.output.weight".to_string(),
-            Tensor::random(vec![hidden_size * 4, hidden_size]),
+            Tensor::random_seeded(vec![intermediate_size, hidden_size], rng),
             layer_id,
         );
 
@@ -122,19 +174,90 @@ impl ModelWeights {
             Tensor::ones(vec![hidden_size]),
             layer_id,
         );
+
+        // Optional projection biases, zero so they start as a no-op
+        if cfg.use_bias {
+            for name in ["attention.query", "attention.key", "attention.value", "attention.output", "ffn.output"] {
+                self.add_parameter(format!("{}.bias", name), Tensor::zeros(vec![hidden_size]), layer_id);
+            }
+            self.add_parameter(
+                "ffn.intermediate.bias".to_string(),
+                Tensor::zeros(vec![intermediate_size]),
+                layer_id,
+            );
+        }
     }
 
-    /// Save weights to a file (simplified)
-    pub fn save(&self, path: &str) -> Result<(), String> {
-        // This would serialize to a file in practice
-        println!("Saving weights to {}", path);
-        Ok(())
+    /// Initialize the output projection; tied configs reuse `embedding.weight` and add nothing
+    pub fn init_output_from_config(&mut self, layer_id: Uuid, cfg: &TransformerConfig, rng: &mut WingbeatRng) {
+        if cfg.tie_embeddings {
+            return;
+        }
+        
+        self.add_parameter(
+            "output.weight".to_string(),
+            Tensor::random_seeded(vec![cfg.vocab_size, cfg.hidden_size], rng),
+            layer_id,
+        );
     }
 
-    /// Load weights from a file (simplified)
-    pub fn load(&mut self, path: &str) -> Result<(), String> {
-        // This would deserialize from a file in practice
-        println!("Loading weights from {}", path);
+    /// Save weights to a safetensors file. Tensors are stored as `F32`; each parameter's
+    /// layer id goes in the file's metadata under the parameter name.
+    pub fn save(&self, path: &str) -> Result<(), WingbeatError> {
+        let mut names: Vec<&String> = self.parameters.keys().collect();
+        names.sort();
+        
+        let bytes: Vec<Vec<u8>> = names
+            .iter()
+            .map(|name| self.parameters[*name].tensor.data.iter().flat_map(|x| x.to_le_bytes()).collect())
+            .collect();
+        let views = names
+            .iter()
+            .zip(&bytes)
+            .map(|(name, data)| {
+                let shape = self.parameters[*name].tensor.shape.clone();
+                TensorView::new(Dtype::F32, shape, data)
+                    .map(|view| (name.as_str(), view))
+                    .map_err(|e| WingbeatError::Serialization(format!("{}: {}", name, e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let metadata = self.parameters
+            .iter()
+            .map(|(name, param)| (name.clone(), param.layer_id.to_string()))
+            .collect();
+        
+        serialize_to_file(views, &Some(metadata), Path::new(path))
+            .map_err(|e| WingbeatError::Serialization(e.to_string()))
+    }
+
+    /// Load parameters from a safetensors file written by [`ModelWeights::save`],
+    /// replacing any parameters with the same names
+    pub fn load(&mut self, path: &str) -> Result<(), WingbeatError> {
+        let buffer = fs::read(path)?;
+        let (_, header) = SafeTensors::read_metadata(&buffer)
+            .map_err(|e| WingbeatError::Serialization(e.to_string()))?;
+        let tensors = SafeTensors::deserialize(&buffer)
+            .map_err(|e| WingbeatError::Serialization(e.to_string()))?;
+        let layer_ids = header.metadata().clone().unwrap_or_default();
+        
+        for (name, view) in tensors.tensors() {
+            if view.dtype() != Dtype::F32 {
+                return Err(WingbeatError::Serialization(format!(
+                    "{}: expected F32 data, found {:?}", name, view.dtype()
+                )));
+            }
+            let data = view.data()
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            let layer_id = layer_ids
+                .get(&name)
+                .and_then(|id| Uuid::parse_str(id).ok())
+                .unwrap_or_else(Uuid::nil);
+            
+            self.add_parameter(name, Tensor::new(view.shape().to_vec(), data), layer_id);
+        }
+        
         Ok(())
     }
 
@@ -144,4 +267,22 @@ impl ModelWeights {
             .map(|p| p.tensor.size())
             .sum()
     }
+
+    /// Parameter count grouped by name prefix (`embedding`, `attention`, `ffn`, `attention_norm`, ...)
+    pub fn parameter_breakdown(&self) -> HashMap<String, usize> {
+        let mut breakdown = HashMap::new();
+        
+        for (name, param) in &self.parameters {
+            let group = name.split('.').next().unwrap_or(name);
+            *breakdown.entry(group.to_string()).or_default() += param.tensor.size();
+        }
+        
+        breakdown
+    }
+}
+
+impl Default for ModelWeights {
+    fn default() -> Self {
+        Self::new()
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,63 @@
+use crate::error::WingbeatError;
+use crate::inference::tensor_ops::{Tensor, DataType};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Serialize, Deserialize};
+
+/// Compact wire form of a tensor: the f32 buffer is sent as base64 of its
+/// little-endian bytes rather than as a JSON array of numbers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TensorPayload {
+    pub shape: Vec<usize>,
+    pub dtype: DataType,
+    pub data: String,
+}
+
+impl TensorPayload {
+    /// Encode a tensor for transport
+    pub fn from_tensor(tensor: &Tensor) -> Self {
+        let bytes: Vec<u8> = tensor.data.iter().flat_map(|v| v.to_le_bytes()).collect();
+        Self {
+            shape: tensor.shape.clone(),
+            dtype: tensor.dtype,
+            data: STANDARD.encode(bytes),
+        }
+    }
+
+    /// Decode back into a tensor, checking the buffer against the shape
+    pub fn to_tensor(&self) -> Result<Tensor, WingbeatError> {
+        let bytes = STANDARD
+            .decode(&self.data)
+            .map_err(|e| WingbeatError::Serialization(format!("invalid tensor payload: {}", e)))?;
+        if bytes.len() % 4 != 0 {
+            return Err(WingbeatError::InvalidShape(format!(
+                "tensor payload of {} bytes is not a whole number of f32 values", bytes.len()
+            )));
+        }
+        
+        let data: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        let expected: usize = self.shape.iter().product();
+        if data.len() != expected {
+            return Err(WingbeatError::ShapeMismatch {
+                expected: vec![expected],
+                got: vec![data.len()],
+            });
+        }
+        
+        Ok(Tensor { shape: self.shape.clone(), data, dtype: self.dtype })
+    }
+
+    /// Encode a tensor straight into a JSON value, e.g. for a task message payload
+    pub fn to_value(tensor: &Tensor) -> serde_json::Value {
+        serde_json::to_value(Self::from_tensor(tensor)).expect("tensor payload serializes")
+    }
+
+    /// Decode a tensor from a JSON value produced by `to_value`
+    pub fn from_value(value: serde_json::Value) -> Result<Tensor, WingbeatError> {
+        let payload: Self = serde_json::from_value(value)
+            .map_err(|e| WingbeatError::Serialization(format!("invalid tensor payload: {}", e)))?;
+        payload.to_tensor()
+    }
+}
@@ -0,0 +1,57 @@
+use crate::inference::tensor_ops::Tensor;
+
+/// Cosine similarity of two tensors with the same number of elements, viewed as flat
+/// vectors. A zero vector has no direction, so its similarity to anything is 0.
+pub fn cosine(a: &Tensor, b: &Tensor) -> Result<f32, String> {
+    if a.size() != b.size() {
+        return Err(format!(
+            "Cannot compare tensors of shape {:?} and {:?}", a.shape, b.shape
+        ));
+    }
+    
+    let dot: f32 = a.data.iter().zip(&b.data).map(|(x, y)| x * y).sum();
+    let norm_a = a.data.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.data.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return Ok(0.0);
+    }
+    
+    Ok((dot / (norm_a * norm_b)).clamp(-1.0, 1.0))
+}
+
+/// Brute-force index of labeled embeddings searched by cosine similarity
+#[derive(Debug, Clone, Default)]
+pub struct NearestNeighbors {
+    entries: Vec<(String, Tensor)>,
+}
+
+impl NearestNeighbors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an embedding under `label`
+    pub fn insert(&mut self, label: impl Into<String>, embedding: Tensor) {
+        self.entries.push((label.into(), embedding));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The `k` stored embeddings most similar to `query`, most similar first
+    pub fn top_k(&self, query: &Tensor, k: usize) -> Result<Vec<(String, f32)>, String> {
+        let mut scored = self.entries
+            .iter()
+            .map(|(label, embedding)| Ok((label.clone(), cosine(query, embedding)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(k);
+        Ok(scored)
+    }
+}
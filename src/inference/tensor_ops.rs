@@ -1,15 +1,24 @@
-use std::collections::HashMap;
+use crate::error::WingbeatError;
+use crate::rng::WingbeatRng;
+use crate::inference::elementwise;
+use rand::Rng;
 use serde::{Serialize, Deserialize};
 
-/// Basic tensor representation for inference
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Inner and output dimensions above which matmul pre-transposes the right operand
+const MATMUL_TRANSPOSE_THRESHOLD: usize = 64;
+
+/// Basic tensor representation for inference. `==` compares shape, dtype and data exactly,
+/// so a tensor containing NaN is never equal to anything, itself included.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Tensor {
     pub shape: Vec<usize>,
     pub data: Vec<f32>,
     pub dtype: DataType,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Element type a tensor represents. Values are always held as f32; the dtype records what
+/// they stand for and is carried through ops by [`DataType::promote`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataType {
     Float32,
     Float16,
@@ -17,6 +26,35 @@ pub enum DataType {
     Int64,
 }
 
+impl DataType {
+    pub fn is_float(self) -> bool {
+        matches!(self, DataType::Float32 | DataType::Float16)
+    }
+
+    /// Result type of an elementwise op on `self` and `other`: equal types are kept, the
+    /// wider of two floats or two ints wins, and a float beats an int
+    pub fn promote(self, other: DataType) -> DataType {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (a, b) if a.is_float() && b.is_float() => DataType::Float32,
+            (a, b) if !a.is_float() && !b.is_float() => DataType::Int64,
+            (a, _) if a.is_float() => a,
+            (_, b) => b,
+        }
+    }
+
+    /// Result type of an op that yields fractions, like softmax: ints become `Float32`
+    pub fn to_float(self) -> DataType {
+        if self.is_float() { self } else { DataType::Float32 }
+    }
+
+    /// Result type of a matmul: ints accumulate in `Float32`, so the product stays there
+    /// until it is cast back with [`Tensor::to_dtype`]
+    pub fn accumulate(self, other: DataType) -> DataType {
+        self.promote(other).to_float()
+    }
+}
+
 impl Tensor {
     pub fn new(shape: Vec<usize>, data: Vec<f32>) -> Self {
         Self {
@@ -45,9 +83,14 @@ impl Tensor {
     }
 
     pub fn random(shape: Vec<usize>) -> Self {
+        Self::random_seeded(shape, &mut WingbeatRng::from_entropy())
+    }
+
+    /// Uniform random tensor in [-1, 1) drawn from a seedable generator
+    pub fn random_seeded(shape: Vec<usize>, rng: &mut WingbeatRng) -> Self {
         let size: usize = shape.iter().product();
         let data: Vec<f32> = (0..size)
-            .map(|_| rand::random::<f32>() * 2.0 - 1.0)
+            .map(|_| rng.gen::<f32>() * 2.0 - 1.0)
             .collect();
         Self {
             shape,
@@ -56,70 +99,198 @@ impl Tensor {
         }
     }
 
+    /// Relabel the tensor as `dtype` without touching its values
+    pub fn with_dtype(mut self, dtype: DataType) -> Self {
+        self.dtype = dtype;
+        self
+    }
+
+    /// Cast to `dtype`, rounding values to what it can represent: integers round to the
+    /// nearest whole number and `Float16` rounds to half precision
+    pub fn to_dtype(&self, dtype: DataType) -> Tensor {
+        let data = match dtype {
+            DataType::Float32 => self.data.clone(),
+            DataType::Float16 => self.data.iter().map(|&x| round_to_f16(x)).collect(),
+            DataType::Int32 | DataType::Int64 => self.data.iter().map(|x| x.round()).collect(),
+        };
+        Tensor::new(self.shape.clone(), data).with_dtype(dtype)
+    }
+
+    /// True if both tensors have the same shape and every pair of elements differs by at
+    /// most `tol`. Equal infinities match; NaN elements never compare equal.
+    pub fn approx_eq(&self, other: &Tensor, tol: f32) -> bool {
+        self.shape == other.shape
+            && self.data.len() == other.data.len()
+            && self.data.iter().zip(&other.data).all(|(a, b)| a == b || (a - b).abs() <= tol)
+    }
+
     pub fn size(&self) -> usize {
         self.data.len()
     }
 
-    pub fn reshape(&mut self, new_shape: Vec<usize>) -> Result<(), String> {
+    pub fn reshape(&mut self, new_shape: Vec<usize>) -> Result<(), WingbeatError> {
         let new_size: usize = new_shape.iter().product();
         if new_size != self.data.len() {
-            return Err("Cannot reshape tensor: size mismatch".to_string());
+            return Err(WingbeatError::ShapeMismatch {
+                expected: self.shape.clone(),
+                got: new_shape,
+            });
         }
         self.shape = new_shape;
         Ok(())
     }
-}
 
-/// Basic tensor operations
-pub trait TensorOps {
-    fn add(&self, other: &Tensor) -> Result<Tensor, String>;
-    fn multiply(&self, other: &Tensor) -> Result<Tensor, String>;
-    fn matmul(&self, other: &Tensor) -> Result<Tensor, String>;
-    fn relu(&self) -> Tensor;
-    fn softmax(&self) -> Tensor;
-    fn transpose(&self) -> Tensor;
-}
+    /// Element at a multi-dimensional index, or None if out of bounds
+    pub fn get(&self, indices: &[usize]) -> Option<f32> {
+        if indices.len() != self.shape.len() {
+            return None;
+        }
+        
+        let mut flat = 0;
+        for (&index, &dim) in indices.iter().zip(self.shape.iter()) {
+            if index >= dim {
+                return None;
+            }
+            flat = flat * dim + index;
+        }
+        
+        self.data.get(flat).copied()
+    }
 
-impl TensorOps for Tensor {
-    fn add(&self, other: &Tensor) -> Result<Tensor, String> {
-        if self.shape != other.shape {
-            return Err("Shape mismatch for addition".to_string());
+    /// Row `i` along the first dimension, keeping it as a leading dim of size 1
+    pub fn row(&self, i: usize) -> Result<Tensor, WingbeatError> {
+        self.slice_dim(0, i, i + 1)
+    }
+
+    /// Elements `start..end` along dimension `dim`
+    pub fn slice_dim(&self, dim: usize, start: usize, end: usize) -> Result<Tensor, WingbeatError> {
+        if dim >= self.shape.len() {
+            return Err(WingbeatError::InvalidShape(format!(
+                "Cannot slice dim {} of a {}-dimensional tensor", dim, self.shape.len()
+            )));
+        }
+        if start > end || end > self.shape[dim] {
+            return Err(WingbeatError::InvalidShape(format!(
+                "Slice {}..{} out of bounds for dim {} of size {}", start, end, dim, self.shape[dim]
+            )));
         }
         
-        let data: Vec<f32> = self.data.iter()
-            .zip(other.data.iter())
-            .map(|(a, b)| a + b)
-            .collect();
-            
-        Ok(Tensor::new(self.shape.clone(), data))
+        let outer: usize = self.shape[..dim].iter().product();
+        let inner: usize = self.shape[dim + 1..].iter().product();
+        let dim_len = self.shape[dim];
+        
+        let mut data = Vec::with_capacity(outer * (end - start) * inner);
+        for o in 0..outer {
+            let base = o * dim_len * inner;
+            data.extend_from_slice(&self.data[base + start * inner..base + end * inner]);
+        }
+        
+        let mut shape = self.shape.clone();
+        shape[dim] = end - start;
+        Ok(Tensor::new(shape, data).with_dtype(self.dtype))
     }
 
-    fn multiply(&self, other: &Tensor) -> Result<Tensor, String> {
-        if self.shape != other.shape {
-            return Err("Shape mismatch for multiplication".to_string());
+    /// Join tensors along an existing dimension; all other dims must match
+    pub fn concat(tensors: &[Tensor], dim: usize) -> Result<Tensor, WingbeatError> {
+        let first = tensors.first()
+            .ok_or_else(|| WingbeatError::InvalidShape("Cannot concat an empty list of tensors".to_string()))?;
+        if dim >= first.shape.len() {
+            return Err(WingbeatError::InvalidShape(format!(
+                "Cannot concat along dim {} of a {}-dimensional tensor", dim, first.shape.len()
+            )));
         }
         
-        let data: Vec<f32> = self.data.iter()
-            .zip(other.data.iter())
-            .map(|(a, b)| a * b)
-            .collect();
-            
-        Ok(Tensor::new(self.shape.clone(), data))
+        for tensor in &tensors[1..] {
+            let compatible = tensor.shape.len() == first.shape.len()
+                && tensor.shape.iter().zip(first.shape.iter())
+                    .enumerate()
+                    .all(|(d, (a, b))| d == dim || a == b);
+            if !compatible {
+                let mut expected = first.shape.clone();
+                if let Some(&len) = tensor.shape.get(dim) {
+                    expected[dim] = len;
+                }
+                return Err(WingbeatError::ShapeMismatch {
+                    expected,
+                    got: tensor.shape.clone(),
+                });
+            }
+        }
+        
+        let outer: usize = first.shape[..dim].iter().product();
+        let inner: usize = first.shape[dim + 1..].iter().product();
+        let total_dim: usize = tensors.iter().map(|t| t.shape[dim]).sum();
+        
+        let mut data = Vec::with_capacity(outer * total_dim * inner);
+        for o in 0..outer {
+            for tensor in tensors {
+                let chunk = tensor.shape[dim] * inner;
+                data.extend_from_slice(&tensor.data[o * chunk..(o + 1) * chunk]);
+            }
+        }
+        
+        let mut shape = first.shape.clone();
+        shape[dim] = total_dim;
+        let dtype = tensors.iter().fold(first.dtype, |dtype, t| dtype.promote(t.dtype));
+        Ok(Tensor::new(shape, data).with_dtype(dtype))
     }
 
-    fn matmul(&self, other: &Tensor) -> Result<Tensor, String> {
-        // Simple matrix multiplication for 2D tensors
+    /// Join equally-shaped tensors along a new dimension inserted at `dim`
+    pub fn stack(tensors: &[Tensor], dim: usize) -> Result<Tensor, WingbeatError> {
+        let first = tensors.first()
+            .ok_or_else(|| WingbeatError::InvalidShape("Cannot stack an empty list of tensors".to_string()))?;
+        if dim > first.shape.len() {
+            return Err(WingbeatError::InvalidShape(format!(
+                "Cannot stack along dim {} of a {}-dimensional tensor", dim, first.shape.len()
+            )));
+        }
+        
+        let mut expanded = Vec::with_capacity(tensors.len());
+        for tensor in tensors {
+            if tensor.shape != first.shape {
+                return Err(WingbeatError::ShapeMismatch {
+                    expected: first.shape.clone(),
+                    got: tensor.shape.clone(),
+                });
+            }
+            let mut shape = tensor.shape.clone();
+            shape.insert(dim, 1);
+            expanded.push(Tensor::new(shape, tensor.data.clone()).with_dtype(tensor.dtype));
+        }
+        
+        Self::concat(&expanded, dim)
+    }
+
+    /// Sizes before, at, and after `dim`, for walking the buffer along one dimension
+    fn split_at_dim(&self, dim: usize) -> (usize, usize, usize) {
+        assert!(dim < self.shape.len(), "dim {} out of range for shape {:?}", dim, self.shape);
+        let outer: usize = self.shape[..dim].iter().product();
+        let inner: usize = self.shape[dim + 1..].iter().product();
+        (outer, self.shape[dim], inner)
+    }
+
+    /// Validate operands for a 2D matmul and return `(m, k, n)`
+    fn matmul_dims(&self, other: &Tensor) -> Result<(usize, usize, usize), WingbeatError> {
         if self.shape.len() != 2 || other.shape.len() != 2 {
-            return Err("MatMul only supports 2D tensors".to_string());
+            return Err(WingbeatError::InvalidShape("MatMul only supports 2D tensors".to_string()));
         }
         
         let (m, k) = (self.shape[0], self.shape[1]);
         let (k2, n) = (other.shape[0], other.shape[1]);
         
         if k != k2 {
-            return Err("Matrix dimensions don't match for multiplication".to_string());
+            return Err(WingbeatError::ShapeMismatch {
+                expected: vec![k, n],
+                got: other.shape.clone(),
+            });
         }
         
+        Ok((m, k, n))
+    }
+
+    /// Reference matmul that strides the right operand column-wise
+    pub fn matmul_naive(&self, other: &Tensor) -> Result<Tensor, WingbeatError> {
+        let (m, k, n) = self.matmul_dims(other)?;
         let mut result = vec![0.0; m * n];
         
         for i in 0..m {
@@ -130,28 +301,127 @@ impl TensorOps for Tensor {
             }
         }
         
-        Ok(Tensor::new(vec![m, n], result))
+        Ok(Tensor::new(vec![m, n], result).with_dtype(self.dtype.accumulate(other.dtype)))
+    }
+
+    /// Matmul that transposes the right operand first so both are read contiguously.
+    /// Accumulates in the same order as [`Tensor::matmul_naive`], so results are identical.
+    pub fn matmul_transposed(&self, other: &Tensor) -> Result<Tensor, WingbeatError> {
+        let (m, k, n) = self.matmul_dims(other)?;
+        let other_t = other.transpose();
+        let mut result = vec![0.0; m * n];
+        
+        for i in 0..m {
+            let row = &self.data[i * k..(i + 1) * k];
+            for j in 0..n {
+                let col = &other_t.data[j * k..(j + 1) * k];
+                let mut acc = 0.0;
+                for (a, b) in row.iter().zip(col) {
+                    acc += a * b;
+                }
+                result[i * n + j] = acc;
+            }
+        }
+        
+        Ok(Tensor::new(vec![m, n], result).with_dtype(self.dtype.accumulate(other.dtype)))
+    }
+}
+
+/// Basic tensor operations
+pub trait TensorOps {
+    fn add(&self, other: &Tensor) -> Result<Tensor, WingbeatError>;
+    fn multiply(&self, other: &Tensor) -> Result<Tensor, WingbeatError>;
+    fn matmul(&self, other: &Tensor) -> Result<Tensor, WingbeatError>;
+    fn relu(&self) -> Tensor;
+    fn gelu(&self) -> Tensor;
+    /// Softmax over the last dimension, normalizing each row independently
+    fn softmax(&self) -> Tensor;
+    fn transpose(&self) -> Tensor;
+    /// Zero each element with probability `p` and scale survivors by `1/(1-p)`; identity unless `train`
+    fn dropout(&self, p: f32, train: bool, rng: &mut WingbeatRng) -> Tensor;
+
+    // Reductions keep the reduced dimension with size 1; they panic if `dim` is out of range
+    fn sum_dim(&self, dim: usize) -> Tensor;
+    fn mean_dim(&self, dim: usize) -> Tensor;
+    fn argmax(&self) -> usize;
+    fn argmax_dim(&self, dim: usize) -> Vec<usize>;
+    /// Log of the softmax along `dim`, computed with the max-subtraction trick
+    fn log_softmax(&self, dim: usize) -> Tensor;
+
+    // In-place variants that reuse the existing buffer; `gelu_` and `mul_scalar_` can produce
+    // fractions, so they turn integer tensors into floats
+    fn add_(&mut self, other: &Tensor) -> Result<(), WingbeatError>;
+    fn relu_(&mut self);
+    fn gelu_(&mut self);
+    fn mul_scalar_(&mut self, scalar: f32);
+}
+
+impl TensorOps for Tensor {
+    fn add(&self, other: &Tensor) -> Result<Tensor, WingbeatError> {
+        if self.shape != other.shape {
+            return Err(WingbeatError::ShapeMismatch {
+                expected: self.shape.clone(),
+                got: other.shape.clone(),
+            });
+        }
+        
+        let data = elementwise::add(&self.data, &other.data);
+            
+        Ok(Tensor::new(self.shape.clone(), data).with_dtype(self.dtype.promote(other.dtype)))
+    }
+
+    fn multiply(&self, other: &Tensor) -> Result<Tensor, WingbeatError> {
+        if self.shape != other.shape {
+            return Err(WingbeatError::ShapeMismatch {
+                expected: self.shape.clone(),
+                got: other.shape.clone(),
+            });
+        }
+        
+        let data = elementwise::mul(&self.data, &other.data);
+            
+        Ok(Tensor::new(self.shape.clone(), data).with_dtype(self.dtype.promote(other.dtype)))
+    }
+
+    fn matmul(&self, other: &Tensor) -> Result<Tensor, WingbeatError> {
+        let (_, k, n) = self.matmul_dims(other)?;
+        
+        // Large right operands are strided badly column-wise, so transpose them first
+        if k > MATMUL_TRANSPOSE_THRESHOLD && n > MATMUL_TRANSPOSE_THRESHOLD {
+            self.matmul_transposed(other)
+        } else {
+            self.matmul_naive(other)
+        }
     }
 
     fn relu(&self) -> Tensor {
+        Tensor::new(self.shape.clone(), elementwise::relu(&self.data)).with_dtype(self.dtype)
+    }
+
+    fn gelu(&self) -> Tensor {
         let data: Vec<f32> = self.data.iter()
-            .map(|&x| x.max(0.0))
+            .map(|&x| gelu_scalar(x))
             .collect();
-        Tensor::new(self.shape.clone(), data)
+        Tensor::new(self.shape.clone(), data).with_dtype(self.dtype.to_float())
     }
 
     fn softmax(&self) -> Tensor {
-        let max_val = self.data.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
-        let exp_data: Vec<f32> = self.data.iter()
-            .map(|&x| (x - max_val).exp())
-            .collect();
-        let sum_exp: f32 = exp_data.iter().sum();
+        let cols = self.shape.last().copied().unwrap_or(self.data.len());
+        if cols == 0 {
+            return self.clone();
+        }
         
-        let data: Vec<f32> = exp_data.iter()
-            .map(|&x| x / sum_exp)
-            .collect();
+        // Each row over the last dimension is normalized on its own
+        let mut data = Vec::with_capacity(self.data.len());
+        for row in self.data.chunks(cols) {
+            let max_val = row.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+            let start = data.len();
+            data.extend(row.iter().map(|&x| (x - max_val).exp()));
+            let sum_exp: f32 = data[start..].iter().sum();
+            data[start..].iter_mut().for_each(|x| *x /= sum_exp);
+        }
             
-        Tensor::new(self.shape.clone(), data)
+        Tensor::new(self.shape.clone(), data).with_dtype(self.dtype.to_float())
     }
 
     fn transpose(&self) -> Tensor {
@@ -168,6 +438,192 @@ impl TensorOps for Tensor {
             }
         }
         
-        Tensor::new(vec![cols, rows], data)
+        Tensor::new(vec![cols, rows], data).with_dtype(self.dtype)
+    }
+
+    fn dropout(&self, p: f32, train: bool, rng: &mut WingbeatRng) -> Tensor {
+        if !train || p <= 0.0 {
+            return self.clone();
+        }
+        if p >= 1.0 {
+            return Tensor::zeros(self.shape.clone()).with_dtype(self.dtype.to_float());
+        }
+        
+        let scale = 1.0 / (1.0 - p);
+        let data: Vec<f32> = self.data.iter()
+            .map(|&x| if rng.gen::<f32>() < p { 0.0 } else { x * scale })
+            .collect();
+        Tensor::new(self.shape.clone(), data).with_dtype(self.dtype.to_float())
+    }
+
+    fn sum_dim(&self, dim: usize) -> Tensor {
+        let (outer, dim_len, inner) = self.split_at_dim(dim);
+        let mut data = vec![0.0; outer * inner];
+        
+        for o in 0..outer {
+            for d in 0..dim_len {
+                let base = (o * dim_len + d) * inner;
+                for i in 0..inner {
+                    data[o * inner + i] += self.data[base + i];
+                }
+            }
+        }
+        
+        let mut shape = self.shape.clone();
+        shape[dim] = 1;
+        Tensor::new(shape, data).with_dtype(self.dtype)
+    }
+
+    fn log_softmax(&self, dim: usize) -> Tensor {
+        let (outer, dim_len, inner) = self.split_at_dim(dim);
+        let mut data = vec![0.0; self.data.len()];
+        
+        for o in 0..outer {
+            for i in 0..inner {
+                let at = |d: usize| (o * dim_len + d) * inner + i;
+                let max_val = (0..dim_len).map(|d| self.data[at(d)]).fold(f32::NEG_INFINITY, f32::max);
+                let log_sum_exp = (0..dim_len)
+                    .map(|d| (self.data[at(d)] - max_val).exp())
+                    .sum::<f32>()
+                    .ln() + max_val;
+                
+                for d in 0..dim_len {
+                    data[at(d)] = self.data[at(d)] - log_sum_exp;
+                }
+            }
+        }
+        
+        Tensor::new(self.shape.clone(), data).with_dtype(self.dtype.to_float())
     }
-} 
\ No newline at end of file
+
+    fn mean_dim(&self, dim: usize) -> Tensor {
+        let mut sum = self.sum_dim(dim).with_dtype(self.dtype.to_float());
+        let count = self.shape[dim];
+        if count > 0 {
+            sum.mul_scalar_(1.0 / count as f32);
+        }
+        sum
+    }
+
+    fn argmax(&self) -> usize {
+        let mut best = 0;
+        for (i, &x) in self.data.iter().enumerate() {
+            if x > self.data[best] {
+                best = i;
+            }
+        }
+        best
+    }
+
+    fn argmax_dim(&self, dim: usize) -> Vec<usize> {
+        let (outer, dim_len, inner) = self.split_at_dim(dim);
+        let mut indices = vec![0; outer * inner];
+        
+        for o in 0..outer {
+            for i in 0..inner {
+                let at = |d: usize| self.data[(o * dim_len + d) * inner + i];
+                let mut best = 0;
+                for d in 1..dim_len {
+                    if at(d) > at(best) {
+                        best = d;
+                    }
+                }
+                indices[o * inner + i] = best;
+            }
+        }
+        
+        indices
+    }
+
+    fn add_(&mut self, other: &Tensor) -> Result<(), WingbeatError> {
+        if self.shape != other.shape {
+            return Err(WingbeatError::ShapeMismatch {
+                expected: self.shape.clone(),
+                got: other.shape.clone(),
+            });
+        }
+        
+        elementwise::add_in_place(&mut self.data, &other.data);
+        self.dtype = self.dtype.promote(other.dtype);
+        
+        Ok(())
+    }
+
+    fn relu_(&mut self) {
+        elementwise::relu_in_place(&mut self.data);
+    }
+
+    fn gelu_(&mut self) {
+        for x in self.data.iter_mut() {
+            *x = gelu_scalar(*x);
+        }
+        self.dtype = self.dtype.to_float();
+    }
+
+    fn mul_scalar_(&mut self, scalar: f32) {
+        for x in self.data.iter_mut() {
+            *x *= scalar;
+        }
+        self.dtype = self.dtype.to_float();
+    }
+}
+
+/// Round to the nearest half-precision value (ties to even), overflowing to infinity
+fn round_to_f16(x: f32) -> f32 {
+    // Halfway between the largest f16 (65504) and the next step up (65536); ties go to the
+    // even side, which is infinity
+    const F16_OVERFLOW: f32 = 65520.0;
+    const F16_MIN_NORMAL: f32 = 6.103_515_6e-5; // 2^-14
+    const F16_SUBNORMAL_STEP: f32 = 5.960_464_5e-8; // 2^-24
+    
+    if !x.is_finite() {
+        return x;
+    }
+    if x.abs() >= F16_OVERFLOW {
+        return f32::INFINITY.copysign(x);
+    }
+    if x.abs() < F16_MIN_NORMAL {
+        return (x / F16_SUBNORMAL_STEP).round_ties_even() * F16_SUBNORMAL_STEP;
+    }
+    
+    // Keep the top 10 of f32's 23 mantissa bits, rounding the dropped 13 to nearest even
+    let bits = x.to_bits();
+    let rounded = bits + 0x0FFF + ((bits >> 13) & 1);
+    f32::from_bits(rounded & !0x1FFF)
+}
+
+/// Tanh approximation of GELU
+fn gelu_scalar(x: f32) -> f32 {
+    const SQRT_2_OVER_PI: f32 = 0.797_884_6;
+    0.5 * x * (1.0 + (SQRT_2_OVER_PI * (x + 0.044715 * x * x * x)).tanh())
+}
+
+/// Mean negative log-likelihood of `targets` under `[num_tokens, vocab]` logits
+pub fn cross_entropy(logits: &Tensor, targets: &[u32]) -> Result<f32, WingbeatError> {
+    if logits.shape.len() != 2 {
+        return Err(WingbeatError::InvalidShape(format!(
+            "cross_entropy expects 2D logits, got {:?}", logits.shape
+        )));
+    }
+    if logits.shape[0] != targets.len() {
+        return Err(WingbeatError::ShapeMismatch {
+            expected: vec![targets.len(), logits.shape[1]],
+            got: logits.shape.clone(),
+        });
+    }
+    let vocab = logits.shape[1];
+    if let Some(&id) = targets.iter().find(|&&t| t as usize >= vocab) {
+        return Err(WingbeatError::TokenOutOfRange { id, vocab_size: vocab });
+    }
+    if targets.is_empty() {
+        return Ok(0.0);
+    }
+    
+    let log_probs = logits.log_softmax(1);
+    let total: f32 = targets.iter()
+        .enumerate()
+        .map(|(row, &target)| -log_probs.data[row * vocab + target as usize])
+        .sum();
+    
+    Ok(total / targets.len() as f32)
+}
@@ -1,34 +1,92 @@
+use crate::error::WingbeatError;
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
 
 /// Token representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Token {
     pub id: u32,
     pub text: String,
-    pub start: usize,
+    pub start: usize, // Byte offsets into the encoded text
     pub end: usize,
 }
 
 /// Basic tokenizer interface
-pub trait Tokenizer {
-    fn encode(&self, text: &str) -> Result<Vec<Token>, String>;
-    fn decode(&self, tokens: &[Token]) -> Result<String, String>;
+pub trait Tokenizer: std::fmt::Debug + Send + Sync {
+    fn encode(&self, text: &str) -> Result<Vec<Token>, WingbeatError>;
+    fn decode(&self, tokens: &[Token]) -> Result<String, WingbeatError>;
     fn vocab_size(&self) -> usize;
 }
 
+/// Vocabulary text for `id`; decoding goes through this rather than trusting `Token::text`
+fn lookup(reverse_vocab: &[String], id: u32) -> Result<&str, WingbeatError> {
+    reverse_vocab
+        .get(id as usize)
+        .map(String::as_str)
+        .ok_or(WingbeatError::TokenOutOfRange { id, vocab_size: reverse_vocab.len() })
+}
+
+/// Text normalization applied to words before vocabulary lookup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormalizerConfig {
+    /// Lowercase every word
+    pub lowercase: bool,
+    /// Apply Unicode NFKC normalization, folding compatibility forms like `ﬁ` into `fi`
+    pub nfkc: bool,
+    /// Remove accents, so `é` becomes `e`
+    pub strip_accents: bool,
+}
+
+impl NormalizerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_lowercase(mut self, lowercase: bool) -> Self {
+        self.lowercase = lowercase;
+        self
+    }
+
+    pub fn with_nfkc(mut self, nfkc: bool) -> Self {
+        self.nfkc = nfkc;
+        self
+    }
+
+    pub fn with_strip_accents(mut self, strip_accents: bool) -> Self {
+        self.strip_accents = strip_accents;
+        self
+    }
+
+    /// Normalize `text`: NFKC first, then accent stripping, then lowercasing
+    pub fn normalize(&self, text: &str) -> String {
+        let mut normalized = if self.nfkc { text.nfkc().collect() } else { text.to_string() };
+        if self.strip_accents {
+            normalized = normalized.nfd().filter(|c| !is_combining_mark(*c)).nfc().collect();
+        }
+        if self.lowercase {
+            normalized = normalized.to_lowercase();
+        }
+        normalized
+    }
+}
+
 /// Simple whitespace-based tokenizer
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimpleTokenizer {
     pub vocab: HashMap<String, u32>,
-    pub reverse_vocab: HashMap<u32, String>,
+    /// Token text indexed by id; ids are assigned densely from zero
+    pub reverse_vocab: Vec<String>,
     pub vocab_size: usize,
+    /// Applied to words both when building the vocabulary and when encoding
+    #[serde(default)]
+    pub normalizer: NormalizerConfig,
 }
 
 impl SimpleTokenizer {
     pub fn new() -> Self {
         let mut vocab = HashMap::new();
-        let mut reverse_vocab = HashMap::new();
         
         // Add basic tokens
         vocab.insert("<pad>".to_string(), 0);
@@ -36,50 +94,82 @@ impl SimpleTokenizer {
         vocab.insert("<sos>".to_string(), 2);
         vocab.insert("<eos>".to_string(), 3);
         
-        reverse_vocab.insert(0, "<pad>".to_string());
-        reverse_vocab.insert(1, "<unk>".to_string());
-        reverse_vocab.insert(2, "<sos>".to_string());
-        reverse_vocab.insert(3, "<eos>".to_string());
+        let reverse_vocab = ["<pad>", "<unk>", "<sos>", "<eos>"].map(String::from).to_vec();
         
         Self {
             vocab,
             reverse_vocab,
             vocab_size: 4,
+            normalizer: NormalizerConfig::default(),
         }
     }
 
+    /// Normalize words with `normalizer`; set this before building the vocabulary
+    pub fn with_normalizer(mut self, normalizer: NormalizerConfig) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
     pub fn build_from_text(&mut self, text: &str, max_vocab_size: usize) {
-        let words: Vec<&str> = text.split_whitespace().collect();
+        self.build_from_text_with_min_count(text, max_vocab_size, 1);
+    }
+
+    /// Like [`SimpleTokenizer::build_from_text`], but words seen fewer than `min_count`
+    /// times are left out of the vocabulary and encode as `<unk>`
+    pub fn build_from_text_with_min_count(&mut self, text: &str, max_vocab_size: usize, min_count: usize) {
+        let words: Vec<String> = text.split_whitespace().map(|w| self.normalizer.normalize(w)).collect();
         let mut word_counts: HashMap<&str, usize> = HashMap::new();
         
         // Count word frequencies
-        for word in words {
+        for word in &words {
             *word_counts.entry(word).or_insert(0) += 1;
         }
         
         // Sort by frequency and add to vocab
         let mut sorted_words: Vec<(&str, usize)> = word_counts.into_iter().collect();
-        sorted_words.sort_by(|a, b| b.1.cmp(&a.1));
+        sorted_words.retain(|&(_, count)| count >= min_count);
+        sorted_words.sort_by_key(|w| std::cmp::Reverse(w.1));
         
-        for (word, _) in sorted_words.iter().take(max_vocab_size - self.vocab_size) {
+        for (word, _) in sorted_words.iter().take(max_vocab_size.saturating_sub(self.vocab_size)) {
             let token_id = self.vocab_size as u32;
             self.vocab.insert(word.to_string(), token_id);
-            self.reverse_vocab.insert(token_id, word.to_string());
+            self.reverse_vocab.push(word.to_string());
             self.vocab_size += 1;
         }
     }
+
+    /// Rebuild the surface text from the tokens' offsets, putting back a space for every byte
+    /// of gap before each token. Input split on runs of spaces round-trips exactly.
+    pub fn decode_with_offsets(&self, tokens: &[Token]) -> String {
+        let mut text = String::new();
+        let mut position = 0;
+        
+        for token in tokens {
+            text.extend(std::iter::repeat_n(' ', token.start.saturating_sub(position)));
+            text.push_str(&token.text);
+            position = token.end;
+        }
+        
+        text
+    }
+}
+
+impl Default for SimpleTokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Tokenizer for SimpleTokenizer {
-    fn encode(&self, text: &str) -> Result<Vec<Token>, String> {
+    fn encode(&self, text: &str) -> Result<Vec<Token>, WingbeatError> {
         let mut tokens = Vec::new();
-        let words: Vec<&str> = text.split_whitespace().collect();
-        let mut current_pos = 0;
         
-        for word in words {
-            let token_id = self.vocab.get(word).copied().unwrap_or(1); // <unk> token
-            let start = current_pos;
-            let end = current_pos + word.len();
+        for word in text.split_whitespace() {
+            let token_id = self.vocab.get(&self.normalizer.normalize(word)).copied().unwrap_or(1); // <unk> token
+            
+            // Words are subslices of `text`, so their byte offsets come straight from the pointers
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            let end = start + word.len();
             
             tokens.push(Token {
                 id: token_id,
@@ -87,21 +177,19 @@ impl Tokenizer for SimpleTokenizer {
                 start,
                 end,
             });
-            
-            current_pos = end + 1; // +1 for space
         }
         
         Ok(tokens)
     }
 
-    fn decode(&self, tokens: &[Token]) -> Result<String, String> {
+    fn decode(&self, tokens: &[Token]) -> Result<String, WingbeatError> {
         let mut text = String::new();
         
         for (i, token) in tokens.iter().enumerate() {
             if i > 0 {
                 text.push(' ');
             }
-            text.push_str(&token.text);
+            text.push_str(lookup(&self.reverse_vocab, token.id)?);
         }
         
         Ok(text)
@@ -113,18 +201,36 @@ impl Tokenizer for SimpleTokenizer {
 }
 
 /// BPE-style tokenizer (simplified)
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BPETokenizer {
     pub vocab: HashMap<String, u32>,
-    pub reverse_vocab: HashMap<u32, String>,
+    /// Token text indexed by id; ids are assigned densely from zero
+    pub reverse_vocab: Vec<String>,
     pub vocab_size: usize,
+    #[serde(with = "merge_list")]
     pub merges: HashMap<(String, String), String>,
 }
 
+/// JSON maps need string keys, so merges are stored as a `[left, right, merged]` list
+mod merge_list {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<S: Serializer>(merges: &HashMap<(String, String), String>, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut list: Vec<(&String, &String, &String)> = merges.iter().map(|((a, b), m)| (a, b, m)).collect();
+        list.sort();
+        list.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<(String, String), String>, D::Error> {
+        let list = Vec::<(String, String, String)>::deserialize(deserializer)?;
+        Ok(list.into_iter().map(|(a, b, m)| ((a, b), m)).collect())
+    }
+}
+
 impl BPETokenizer {
     pub fn new() -> Self {
         let mut vocab = HashMap::new();
-        let mut reverse_vocab = HashMap::new();
         
         // Add basic tokens
         vocab.insert("<pad>".to_string(), 0);
@@ -132,10 +238,7 @@ impl BPETokenizer {
         vocab.insert("<sos>".to_string(), 2);
         vocab.insert("<eos>".to_string(), 3);
         
-        reverse_vocab.insert(0, "<pad>".to_string());
-        reverse_vocab.insert(1, "<unk>".to_string());
-        reverse_vocab.insert(2, "<sos>".to_string());
-        reverse_vocab.insert(3, "<eos>".to_string());
+        let reverse_vocab = ["<pad>", "<unk>", "<sos>", "<eos>"].map(String::from).to_vec();
         
         Self {
             vocab,
@@ -145,7 +248,7 @@ impl BPETokenizer {
         }
     }
 
-    pub fn train(&mut self, text: &str, num_merges: usize) {
+    pub fn train(&mut self, text: &str, _num_merges: usize) {
         // Simplified BPE training
         let mut word_counts: HashMap<String, usize> = HashMap::new();
         
@@ -161,7 +264,7 @@ impl BPETokenizer {
                 if !self.vocab.contains_key(&ch_str) {
                     let token_id = self.vocab_size as u32;
                     self.vocab.insert(ch_str.clone(), token_id);
-                    self.reverse_vocab.insert(token_id, ch_str);
+                    self.reverse_vocab.push(ch_str);
                     self.vocab_size += 1;
                 }
             }
@@ -169,8 +272,14 @@ impl BPETokenizer {
     }
 }
 
+impl Default for BPETokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Tokenizer for BPETokenizer {
-    fn encode(&self, text: &str) -> Result<Vec<Token>, String> {
+    fn encode(&self, text: &str) -> Result<Vec<Token>, WingbeatError> {
         let mut tokens = Vec::new();
         let mut current_pos = 0;
         
@@ -191,11 +300,11 @@ impl Tokenizer for BPETokenizer {
         Ok(tokens)
     }
 
-    fn decode(&self, tokens: &[Token]) -> Result<String, String> {
+    fn decode(&self, tokens: &[Token]) -> Result<String, WingbeatError> {
         let mut text = String::new();
         
         for token in tokens {
-            text.push_str(&token.text);
+            text.push_str(lookup(&self.reverse_vocab, token.id)?);
         }
         
         Ok(text)
@@ -206,6 +315,23 @@ impl Tokenizer for BPETokenizer {
     }
 }
 
+/// A concrete tokenizer, serializable so it can be saved alongside a model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenizerKind {
+    Simple(SimpleTokenizer),
+    Bpe(BPETokenizer),
+}
+
+impl TokenizerKind {
+    pub fn into_tokenizer(self) -> Box<dyn Tokenizer> {
+        match self {
+            TokenizerKind::Simple(tokenizer) => Box::new(tokenizer),
+            TokenizerKind::Bpe(tokenizer) => Box::new(tokenizer),
+        }
+    }
+}
+
 /// Tokenizer factory
 pub struct TokenizerFactory;
 
@@ -1,17 +1,28 @@
+pub mod error;
+pub mod rng;
 pub mod core;
 pub mod swarm;
 pub mod computation;
 pub mod inference;
+pub mod output;
+#[cfg(feature = "prometheus")]
+pub mod metrics;
 
-pub use core::subgraph::{Subgraph, SubgraphId, SubgraphType};
-pub use swarm::tornado::{TornadoSwarm, Tornado};
+pub use error::WingbeatError;
+pub use rng::WingbeatRng;
+pub use output::{OutputMode, set_output_mode, output_mode, announce};
+pub use core::subgraph::{Subgraph, SubgraphId, SubgraphType, CompatibilityRules};
+pub use swarm::tornado::{TornadoSwarm, Tornado, Vec3, WorldBounds, SwarmSnapshot};
+pub use swarm::metrics::{SwarmMetrics, SwarmMetricsSnapshot};
+pub use swarm::config::SwarmConfig;
 pub use computation::{
     model_decomposer::{ModelDecomposer, LayerType, DecompositionStrategy},
     enhanced_processor::EnhancedProcessor,
-    prompt_processor::PromptProcessor,
+    prompt_processor::{PromptProcessor, Priority},
 };
 pub use inference::{
-    InferenceEngine, Tensor, ModelWeights, LayerOperation, Tokenizer,
+    InferenceEngine, ForwardHook, LayerTiming, ModelBundle, Tensor, ModelWeights, LayerOperation, LayerContext, Tokenizer,
+    TransformerConfig, Activation, BatchConfig,
     tensor_ops::TensorOps,
     layer_ops::LayerFactory,
     tokenizer::TokenizerFactory,
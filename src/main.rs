@@ -1,23 +1,24 @@
-use wingbeat::{TornadoSwarm, PromptProcessor, Vec3};
+use wingbeat::{TornadoSwarm, PromptProcessor, Vec3, OutputMode, announce, output_mode};
+use wingbeat::output::render;
 use std::sync::Arc;
 use colored::*;
 use tokio::time::{sleep, Duration};
 
 #[tokio::main]
 async fn main() {
-    // Initialize tracing for better async debugging
-    tracing_subscriber::fmt::init();
+    // Honour NO_COLOR / WINGBEAT_OUTPUT and report swarm activity through tracing
+    wingbeat::output::init_from_env();
     
-    println!("{}", "╔══════════════════════════════════════════╗".bright_cyan());
-    println!("{}", "║           WINGBEAT SWARM ENGINE          ║".bright_cyan().bold());
-    println!("{}", "╚══════════════════════════════════════════╝".bright_cyan());
-    println!();
+    announce("╔══════════════════════════════════════════╗".bright_cyan());
+    announce("║           WINGBEAT SWARM ENGINE          ║".bright_cyan().bold());
+    announce("╚══════════════════════════════════════════╝".bright_cyan());
+    announce("");
     
     // Initialize the tornado swarm
     let swarm = Arc::new(TornadoSwarm::new());
     
     // Spawn initial tornadoes at different positions
-    println!("{}", "Initializing tornado swarm...".yellow());
+    announce("Initializing tornado swarm...".yellow());
     for i in 0..3 {
         let position = Vec3::new(
             (i as f32) * 15.0,
@@ -37,45 +38,53 @@ async fn main() {
         "Distributed computation through whirlwinds",
     ];
     
-    println!();
-    println!("{}", "═══════════════════════════════════════════".bright_blue());
-    println!("{}", "Processing prompts through the swarm...".bright_green().bold());
-    println!("{}", "═══════════════════════════════════════════".bright_blue());
-    println!();
+    announce("");
+    announce("═══════════════════════════════════════════".bright_blue());
+    announce("Processing prompts through the swarm...".bright_green().bold());
+    announce("═══════════════════════════════════════════".bright_blue());
+    announce("");
     
     for prompt in prompts {
         // Send prompt into the swarm
-        let prompt_id = processor.send_prompt(prompt).await;
+        let prompt_id = match processor.send_prompt(prompt).await {
+            Ok(id) => id,
+            Err(e) => {
+                eprintln!("{}", format!("❌ Failed to send prompt: {}", e).red());
+                continue;
+            }
+        };
         
         // Simulate processing time with visual feedback
         for _i in 0..5 {
             processor.process_step(0.1).await;
-            print!("{}", ".".bright_yellow());
-            use std::io::{self, Write};
-            io::stdout().flush().unwrap();
+            if let Some(dot) = render(".".bright_yellow()) {
+                print!("{}", dot);
+                use std::io::{self, Write};
+                io::stdout().flush().unwrap();
+            }
             sleep(Duration::from_millis(200)).await;
         }
-        println!();
+        announce("");
         
         // Collect results
         if let Some(result) = processor.collect_results(prompt_id).await {
-            println!("{}", format!("📊 Result: {}", result).bright_green());
+            announce(format!("📊 Result: {}", result).bright_green());
         }
         
-        println!("{}", "───────────────────────────────────────────".bright_black());
-        println!();
+        announce("───────────────────────────────────────────".bright_black());
+        announce("");
     }
     
     // Show final swarm visualization
-    println!();
-    println!("{}", "╔══════════════════════════════════════════╗".bright_magenta());
-    println!("{}", "║         SWARM VISUALIZATION              ║".bright_magenta().bold());
-    println!("{}", "╚══════════════════════════════════════════╝".bright_magenta());
+    announce("");
+    announce("╔══════════════════════════════════════════╗".bright_magenta());
+    announce("║         SWARM VISUALIZATION              ║".bright_magenta().bold());
+    announce("╚══════════════════════════════════════════╝".bright_magenta());
     
     visualize_swarm().await;
     
-    println!();
-    println!("{}", "✨ Wingbeat swarm processing complete!".bright_green().bold());
+    announce("");
+    announce("✨ Wingbeat swarm processing complete!".bright_green().bold());
 }
 
 async fn visualize_swarm() {
@@ -109,9 +118,15 @@ async fn visualize_swarm() {
         "#,
     ];
     
+    if output_mode() == OutputMode::Quiet {
+        return;
+    }
+    
     for frame in frames {
-        print!("\x1B[2J\x1B[1;1H"); // Clear screen
-        println!("{}", frame.bright_cyan());
+        if output_mode() == OutputMode::Color {
+            print!("\x1B[2J\x1B[1;1H"); // Clear screen
+        }
+        announce(frame.bright_cyan());
         sleep(Duration::from_millis(500)).await;
     }
 }
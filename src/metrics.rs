@@ -0,0 +1,46 @@
+//! Prometheus exposition of swarm and inference metrics (requires the `prometheus` feature)
+
+use crate::inference::metrics::{InferenceMetrics, InferenceMetricsSnapshot};
+use crate::swarm::metrics::{SwarmMetrics, SwarmMetricsSnapshot};
+use axum::{routing::get, Router};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Render metrics in the Prometheus text exposition format
+pub fn render_prometheus(swarm: &SwarmMetricsSnapshot, inference: &InferenceMetricsSnapshot) -> String {
+    let metrics: [(&str, &str, &str, String); 7] = [
+        ("wingbeat_subgraphs_swept_total", "counter", "Subgraphs swept up by tornadoes", swarm.subgraphs_swept.to_string()),
+        ("wingbeat_subgraphs_released_total", "counter", "Subgraphs released by tornadoes", swarm.subgraphs_released.to_string()),
+        ("wingbeat_spins_total", "counter", "Tornado spin steps", swarm.spins.to_string()),
+        ("wingbeat_connections_detected_total", "counter", "Compatible subgraph pairs found while spinning", swarm.connections_detected.to_string()),
+        ("wingbeat_tokens_generated_total", "counter", "Tokens produced by inference", inference.tokens_generated.to_string()),
+        ("wingbeat_forward_passes_total", "counter", "Completed forward passes", inference.forward_passes.to_string()),
+        ("wingbeat_avg_forward_ms", "gauge", "Average forward pass latency in milliseconds", inference.avg_forward_ms.to_string()),
+    ];
+    
+    let mut out = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+    out
+}
+
+/// Router serving `GET /metrics`
+pub fn router(swarm: Arc<SwarmMetrics>, inference: Arc<InferenceMetrics>) -> Router {
+    Router::new().route(
+        "/metrics",
+        get(move || async move { render_prometheus(&swarm.snapshot(), &inference.snapshot()) }),
+    )
+}
+
+/// Serve the metrics endpoint on `addr` until the task is dropped
+pub async fn serve(
+    addr: &str,
+    swarm: Arc<SwarmMetrics>,
+    inference: Arc<InferenceMetrics>,
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(swarm, inference)).await
+}
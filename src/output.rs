@@ -0,0 +1,79 @@
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How the decorative console output of the binary and examples is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// ANSI colors and styling
+    Color,
+    /// The same text without escape sequences, for logs and CI
+    Plain,
+    /// No decorative output at all
+    Quiet,
+}
+
+static OUTPUT_MODE: AtomicU8 = AtomicU8::new(0);
+
+impl OutputMode {
+    /// Pick a mode from the environment: `WINGBEAT_OUTPUT=color|plain|quiet` wins, then
+    /// `NO_COLOR` or a non-terminal stdout select `Plain`, otherwise `Color`
+    pub fn from_env() -> Self {
+        match std::env::var("WINGBEAT_OUTPUT").as_deref() {
+            Ok("color") => return OutputMode::Color,
+            Ok("plain") => return OutputMode::Plain,
+            Ok("quiet") => return OutputMode::Quiet,
+            _ => {}
+        }
+        
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        if no_color || !std::io::stdout().is_terminal() {
+            OutputMode::Plain
+        } else {
+            OutputMode::Color
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => OutputMode::Plain,
+            2 => OutputMode::Quiet,
+            _ => OutputMode::Color,
+        }
+    }
+}
+
+/// Set the process-wide output mode; this also switches `colored` styling on or off
+pub fn set_output_mode(mode: OutputMode) {
+    OUTPUT_MODE.store(mode as u8, Ordering::Relaxed);
+    colored::control::set_override(mode == OutputMode::Color);
+}
+
+/// Set up an entry point: pick the output mode from the environment and install a tracing
+/// subscriber that only emits ANSI colors in `Color` mode. Call once, at the start of `main`.
+pub fn init_from_env() {
+    set_output_mode(OutputMode::from_env());
+    tracing_subscriber::fmt()
+        .with_ansi(output_mode() == OutputMode::Color)
+        .init();
+}
+
+/// The current process-wide output mode
+pub fn output_mode() -> OutputMode {
+    OutputMode::from_u8(OUTPUT_MODE.load(Ordering::Relaxed))
+}
+
+/// Render a decorative line for the current mode, or `None` when quiet
+pub fn render<T: fmt::Display>(line: T) -> Option<String> {
+    match output_mode() {
+        OutputMode::Quiet => None,
+        OutputMode::Color | OutputMode::Plain => Some(line.to_string()),
+    }
+}
+
+/// Print a decorative line unless output is quiet
+pub fn announce<T: fmt::Display>(line: T) {
+    if let Some(line) = render(line) {
+        println!("{}", line);
+    }
+}
@@ -0,0 +1,48 @@
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use uuid::{Builder, Uuid};
+
+/// Seedable random number generator for reproducible swarms and weights
+#[derive(Debug, Clone)]
+pub struct WingbeatRng {
+    inner: StdRng,
+}
+
+impl WingbeatRng {
+    /// Create a deterministic generator from a seed
+    pub fn seed_from_u64(seed: u64) -> Self {
+        Self {
+            inner: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Create a generator seeded from OS entropy
+    pub fn from_entropy() -> Self {
+        Self {
+            inner: StdRng::from_entropy(),
+        }
+    }
+
+    /// Generate a random (v4) UUID from this generator's stream
+    pub fn gen_uuid(&mut self) -> Uuid {
+        Builder::from_random_bytes(self.gen()).into_uuid()
+    }
+}
+
+impl RngCore for WingbeatRng {
+    fn next_u32(&mut self) -> u32 {
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.inner.try_fill_bytes(dest)
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Serialize, Deserialize};
+use std::ops::RangeInclusive;
+
+/// Tunable dynamics for tornadoes spawned into a swarm. Ranges are inclusive, so a fixed
+/// value is written as `5.0..=5.0`; a range whose start exceeds its end panics on spawn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwarmConfig {
+    pub radius_range: RangeInclusive<f32>,
+    pub angular_velocity_range: RangeInclusive<f32>,
+    pub height_range: RangeInclusive<f32>,
+    /// Largest random wander per axis, per unit of simulated time
+    pub drift: f32,
+}
+
+impl SwarmConfig {
+    pub fn new() -> Self {
+        Self {
+            radius_range: 5.0..=20.0,
+            angular_velocity_range: 0.5..=2.0,
+            height_range: 10.0..=50.0,
+            drift: 1.0,
+        }
+    }
+
+    pub fn with_radius_range(mut self, radius_range: RangeInclusive<f32>) -> Self {
+        self.radius_range = radius_range;
+        self
+    }
+
+    pub fn with_angular_velocity_range(mut self, angular_velocity_range: RangeInclusive<f32>) -> Self {
+        self.angular_velocity_range = angular_velocity_range;
+        self
+    }
+
+    pub fn with_height_range(mut self, height_range: RangeInclusive<f32>) -> Self {
+        self.height_range = height_range;
+        self
+    }
+
+    pub fn with_drift(mut self, drift: f32) -> Self {
+        self.drift = drift;
+        self
+    }
+}
+
+impl Default for SwarmConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,41 @@
+use serde::{Serialize, Deserialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Throughput counters shared by a swarm and its tornadoes
+#[derive(Debug, Default)]
+pub struct SwarmMetrics {
+    pub subgraphs_swept: AtomicU64,
+    pub subgraphs_released: AtomicU64,
+    pub spins: AtomicU64,
+    pub connections_detected: AtomicU64,
+}
+
+/// Point-in-time copy of [`SwarmMetrics`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwarmMetricsSnapshot {
+    pub subgraphs_swept: u64,
+    pub subgraphs_released: u64,
+    pub spins: u64,
+    pub connections_detected: u64,
+}
+
+impl SwarmMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `n` to a counter; relaxed ordering is enough for monotonic statistics
+    pub fn record(counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Read all counters
+    pub fn snapshot(&self) -> SwarmMetricsSnapshot {
+        SwarmMetricsSnapshot {
+            subgraphs_swept: self.subgraphs_swept.load(Ordering::Relaxed),
+            subgraphs_released: self.subgraphs_released.load(Ordering::Relaxed),
+            spins: self.spins.load(Ordering::Relaxed),
+            connections_detected: self.connections_detected.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -1 +1,3 @@
-pub mod tornado; 
\ No newline at end of file
+pub mod tornado;
+pub mod metrics;
+pub mod config;
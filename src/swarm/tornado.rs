@@ -1,24 +1,45 @@
 use crate::core::subgraph::Subgraph;
-use std::collections::HashMap;
+use crate::swarm::metrics::{SwarmMetrics, SwarmMetricsSnapshot};
+use crate::swarm::config::SwarmConfig;
+use crate::rng::WingbeatRng;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
-use tokio::sync::RwLock;
-use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use rand::Rng;
-use colored::*;
+use serde::{Serialize, Deserialize};
+use tracing::{debug, info, instrument, warn};
+use futures::stream::{self, StreamExt};
 
-/// Represents a tornado/whirlwind in the swarm
+/// Tornadoes counted concurrently by `TornadoSwarm::total_subgraphs`
+const TOTAL_SUBGRAPHS_CONCURRENCY: usize = 8;
+
+/// Represents a tornado/whirlwind in the swarm.
+///
+/// Lock ordering: a task that holds more than one subgraph lock at a time must acquire
+/// them in ascending `Uuid` order. `spin` and `merge_subgraphs` both follow this, so
+/// concurrent spins and merges over overlapping subgraphs cannot deadlock.
 #[derive(Debug)]
 pub struct Tornado {
     pub id: Uuid,
     pub eye: Vec3,  // Center of the tornado
+    pub velocity: Vec3,
     pub radius: f32,
     pub angular_velocity: f32,
     pub height: f32,
     pub subgraphs: Arc<RwLock<HashMap<Uuid, Arc<RwLock<Subgraph>>>>>,
+    pub metrics: Arc<SwarmMetrics>,
+    /// Bounded intake queue; when set, `sweep_up` enqueues instead of inserting directly
+    pub intake: Option<mpsc::Sender<Arc<RwLock<Subgraph>>>>,
+    /// Merge connectable pairs found by `spin` into a single subgraph
+    pub merge_on_connect: bool,
+    /// Tornado that absorbed this one; subgraphs arriving afterwards are forwarded to it
+    absorbed_into: Arc<Mutex<Option<Tornado>>>,
 }
 
 /// 3D position for tornado dynamics
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -35,45 +56,255 @@ impl Vec3 {
          (self.y - other.y).powi(2) + 
          (self.z - other.z).powi(2)).sqrt()
     }
+
+    pub fn scale(self, factor: f32) -> Vec3 {
+        Vec3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+
+    pub fn dot(self, other: Vec3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Unit vector in the same direction; the zero vector is returned unchanged
+    pub fn normalize(self) -> Vec3 {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self.scale(1.0 / length)
+        }
+    }
+}
+
+impl std::ops::Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl std::ops::Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl std::ops::Mul<f32> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, factor: f32) -> Vec3 {
+        self.scale(factor)
+    }
+}
+
+/// Cloning a tornado copies its physics but shares its subgraph map, metrics, intake
+/// queue and absorption state with the original, so subgraphs swept into either are visible to both.
+/// Use [`Tornado::deep_clone`] for an independent copy.
+impl Clone for Tornado {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            eye: self.eye,
+            velocity: self.velocity,
+            radius: self.radius,
+            angular_velocity: self.angular_velocity,
+            height: self.height,
+            subgraphs: Arc::clone(&self.subgraphs),
+            metrics: Arc::clone(&self.metrics),
+            intake: self.intake.clone(),
+            merge_on_connect: self.merge_on_connect,
+            absorbed_into: Arc::clone(&self.absorbed_into),
+        }
+    }
+}
+
+/// Axis-aligned box that tornado positions are kept inside
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WorldBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl WorldBounds {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, position: Vec3) -> bool {
+        (self.min.x..=self.max.x).contains(&position.x)
+            && (self.min.y..=self.max.y).contains(&position.y)
+            && (self.min.z..=self.max.z).contains(&position.z)
+    }
+
+    /// Bounce a position that left the box back inside, inverting the velocity on each
+    /// axis it crossed. Overshoots larger than the box itself end up clamped to the wall.
+    pub fn reflect(&self, position: Vec3, velocity: Vec3) -> (Vec3, Vec3) {
+        let (x, vx) = reflect_axis(position.x, velocity.x, self.min.x, self.max.x);
+        let (y, vy) = reflect_axis(position.y, velocity.y, self.min.y, self.max.y);
+        let (z, vz) = reflect_axis(position.z, velocity.z, self.min.z, self.max.z);
+        (Vec3::new(x, y, z), Vec3::new(vx, vy, vz))
+    }
+}
+
+fn reflect_axis(position: f32, velocity: f32, min: f32, max: f32) -> (f32, f32) {
+    if position < min {
+        ((2.0 * min - position).min(max), -velocity)
+    } else if position > max {
+        ((2.0 * max - position).max(min), -velocity)
+    } else {
+        (position, velocity)
+    }
 }
 
 impl Tornado {
     pub fn new(position: Vec3) -> Self {
+        Self::new_seeded(position, &mut WingbeatRng::from_entropy())
+    }
+
+    /// Create a tornado whose id and dynamics come from a seedable generator
+    pub fn new_seeded(position: Vec3, rng: &mut WingbeatRng) -> Self {
+        Self::with_config_seeded(position, &SwarmConfig::default(), rng)
+    }
+
+    /// Create a tornado with dynamics drawn from the ranges in `config`
+    pub fn with_config_seeded(position: Vec3, config: &SwarmConfig, rng: &mut WingbeatRng) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: rng.gen_uuid(),
             eye: position,
-            radius: rand::thread_rng().gen_range(5.0..20.0),
-            angular_velocity: rand::thread_rng().gen_range(0.5..2.0),
-            height: rand::thread_rng().gen_range(10.0..50.0),
+            velocity: Vec3::default(),
+            radius: rng.gen_range(config.radius_range.clone()),
+            angular_velocity: rng.gen_range(config.angular_velocity_range.clone()),
+            height: rng.gen_range(config.height_range.clone()),
             subgraphs: Arc::new(RwLock::new(HashMap::new())),
+            metrics: Arc::new(SwarmMetrics::new()),
+            intake: None,
+            merge_on_connect: false,
+            absorbed_into: Arc::default(),
+        }
+    }
+
+    /// Have `spin` merge every connectable pair it finds
+    pub fn with_merge_on_connect(mut self, merge_on_connect: bool) -> Self {
+        self.merge_on_connect = merge_on_connect;
+        self
+    }
+
+    /// Route `sweep_up` through a queue of `capacity` subgraphs, drained by a spawned task
+    /// that takes one subgraph every `drain_interval`. Senders await while the queue is full,
+    /// so a saturated tornado applies backpressure rather than dropping work.
+    /// Must be called from within a tokio runtime.
+    pub fn with_queue(mut self, capacity: usize, drain_interval: Duration) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<Arc<RwLock<Subgraph>>>(capacity);
+        let destination = Self { intake: None, ..self.clone() };
+        let tornado_id = self.id;
+        
+        tokio::spawn(async move {
+            while let Some(subgraph) = receiver.recv().await {
+                destination.insert_subgraph(subgraph).await;
+                debug!(tornado_id = %tornado_id, "drained queued subgraph");
+                tokio::time::sleep(drain_interval).await;
+            }
+        });
+        
+        self.intake = Some(sender);
+        self
+    }
+
+    /// Copy the tornado along with its subgraph set; each subgraph and its compute graph
+    /// is duplicated, so changes to the copy never reach the original. Metrics stay
+    /// shared and the copy has no intake queue.
+    pub async fn deep_clone(&self) -> Self {
+        let mut subgraphs = HashMap::new();
+        for (id, subgraph) in self.subgraphs.read().await.iter() {
+            let subgraph = subgraph.read().await;
+            let mut copy = subgraph.clone();
+            copy.graph = Arc::new(RwLock::new(subgraph.graph.read().await.clone()));
+            subgraphs.insert(*id, Arc::new(RwLock::new(copy)));
+        }
+        
+        Self {
+            subgraphs: Arc::new(RwLock::new(subgraphs)),
+            intake: None,
+            absorbed_into: Arc::default(),
+            ..self.clone()
         }
     }
 
-    /// Sweep up a subgraph into the tornado
+    /// Number of subgraphs waiting in the intake queue (always 0 without a queue)
+    pub fn queue_depth(&self) -> usize {
+        self.intake
+            .as_ref()
+            .map(|sender| sender.max_capacity() - sender.capacity())
+            .unwrap_or(0)
+    }
+
+    /// Sweep up a subgraph into the tornado, waiting for queue space if the tornado has one
+    #[instrument(skip_all, fields(tornado_id = %self.id))]
     pub async fn sweep_up(&self, subgraph: Arc<RwLock<Subgraph>>) {
-        let mut subgraphs = self.subgraphs.write().await;
         let sg_id = subgraph.read().await.id;
+        debug!(subgraph_id = %sg_id, "sweeping up subgraph");
         
-        println!("{}", format!("🌪️  Tornado {} sweeping up subgraph {}", 
-            self.id.to_string()[..8].cyan(), 
-            sg_id.to_string()[..8].yellow()
-        ));
-        
-        subgraphs.insert(sg_id, subgraph);
+        match &self.intake {
+            Some(sender) => {
+                if let Err(mpsc::error::SendError(subgraph)) = sender.send(subgraph).await {
+                    // The drain task is gone; hold the subgraph directly rather than lose it
+                    warn!(subgraph_id = %sg_id, "intake queue closed, inserting directly");
+                    self.insert_subgraph(subgraph).await;
+                }
+            }
+            None => self.insert_subgraph(subgraph).await,
+        }
+    }
+
+    /// Insert into this tornado's map, or into whichever tornado ended up absorbing it.
+    /// The absorber is checked under the map's write lock, which `TornadoSwarm::absorb`
+    /// also holds while setting it, so nothing lands in a map that has already been drained.
+    async fn insert_subgraph(&self, subgraph: Arc<RwLock<Subgraph>>) {
+        let sg_id = subgraph.read().await.id;
+        let mut destination = self.clone();
+        loop {
+            let mut subgraphs = destination.subgraphs.write().await;
+            let absorber = destination.absorbed_into.lock().unwrap().clone();
+            match absorber {
+                Some(absorber) => {
+                    drop(subgraphs);
+                    destination = absorber;
+                }
+                None => {
+                    subgraphs.insert(sg_id, subgraph);
+                    SwarmMetrics::record(&destination.metrics.subgraphs_swept, 1);
+                    return;
+                }
+            }
+        }
     }
 
-    /// Spin the tornado, causing subgraphs to interact
-    pub async fn spin(&self) {
+    /// Spin the tornado, causing subgraphs to interact. Returns every pair of held subgraphs
+    /// that can connect, lower id first, in ascending id order. With `merge_on_connect` set,
+    /// each pair is then merged into its lower-id subgraph and the other one is dropped from
+    /// the tornado; a subgraph takes part in at most one merge per spin.
+    #[instrument(skip_all, fields(tornado_id = %self.id))]
+    pub async fn spin(&self) -> Vec<(Uuid, Uuid)> {
         let subgraphs = self.subgraphs.read().await;
+        let mut connections = Vec::new();
         
+        SwarmMetrics::record(&self.metrics.spins, 1);
         if subgraphs.len() < 2 {
-            return;
+            return connections;
         }
 
-        println!("{}", "🌀 Tornado spinning, subgraphs interacting...".bright_blue());
+        debug!(subgraph_count = subgraphs.len(), "spinning, subgraphs interacting");
         
-        // Randomly select pairs of subgraphs to potentially merge or split
-        let ids: Vec<Uuid> = subgraphs.keys().cloned().collect();
+        // Sorted so each pair is locked lower id first
+        let mut ids: Vec<Uuid> = subgraphs.keys().cloned().collect();
+        ids.sort();
         
         for i in 0..ids.len() {
             for j in i+1..ids.len() {
@@ -84,73 +315,424 @@ impl Tornado {
                 let sg2_read = sg2.read().await;
                 
                 if sg1_read.can_connect_with(&sg2_read) {
-                    println!("{}", format!("⚡ Subgraphs {} and {} connecting!", 
-                        ids[i].to_string()[..8].green(),
-                        ids[j].to_string()[..8].green()
-                    ));
+                    SwarmMetrics::record(&self.metrics.connections_detected, 1);
+                    debug!(subgraph_a = %ids[i], subgraph_b = %ids[j], "subgraphs connecting");
+                    connections.push((ids[i], ids[j]));
                 }
             }
         }
+        drop(subgraphs);
+        
+        if self.merge_on_connect {
+            let mut merged = HashSet::new();
+            for &(target, source) in &connections {
+                if merged.contains(&target) || merged.contains(&source) {
+                    continue;
+                }
+                match self.merge_subgraphs(target, source).await {
+                    Ok(()) => {
+                        self.subgraphs.write().await.remove(&source);
+                        merged.extend([target, source]);
+                        debug!(target = %target, source = %source, "merged connected subgraphs");
+                    }
+                    Err(e) => warn!(target = %target, source = %source, error = %e, "failed to merge connected subgraphs"),
+                }
+            }
+        }
+        
+        connections
+    }
+
+    /// Merge the held subgraph `source` into `target`, locking both in id order.
+    /// `source` stays in the tornado; callers decide whether to release it.
+    pub async fn merge_subgraphs(&self, target: Uuid, source: Uuid) -> Result<(), String> {
+        if target == source {
+            return Err(format!("Cannot merge subgraph {} with itself", target));
+        }
+        
+        let (target_sg, source_sg) = {
+            let subgraphs = self.subgraphs.read().await;
+            let lookup = |id: Uuid| {
+                subgraphs.get(&id)
+                    .cloned()
+                    .ok_or_else(|| format!("Subgraph {} not held by tornado {}", id, self.id))
+            };
+            (lookup(target)?, lookup(source)?)
+        };
+        
+        let (mut target_guard, source_guard) = if target < source {
+            let target_guard = target_sg.write().await;
+            (target_guard, source_sg.read().await)
+        } else {
+            let source_guard = source_sg.read().await;
+            (target_sg.write().await, source_guard)
+        };
+        
+        debug!(target = %target, source = %source, "merging subgraphs");
+        target_guard.merge(source_guard.clone()).await
     }
 
     /// Release subgraphs from the tornado
+    #[instrument(skip_all, fields(tornado_id = %self.id))]
     pub async fn release(&self, count: usize) -> Vec<Arc<RwLock<Subgraph>>> {
         let mut subgraphs = self.subgraphs.write().await;
         let mut released = Vec::new();
         
         let keys: Vec<Uuid> = subgraphs.keys().cloned().collect();
         
-        for i in 0..count.min(keys.len()) {
-            if let Some(sg) = subgraphs.remove(&keys[i]) {
-                println!("{}", format!("💨 Releasing subgraph {} from tornado", 
-                    keys[i].to_string()[..8].bright_yellow()
-                ));
+        for key in keys.iter().take(count) {
+            if let Some(sg) = subgraphs.remove(key) {
+                debug!(subgraph_id = %key, "releasing subgraph");
                 released.push(sg);
             }
         }
         
+        SwarmMetrics::record(&self.metrics.subgraphs_released, released.len() as u64);
         released
     }
+
+    /// Whether the tornado currently holds the subgraph with `id`
+    pub async fn contains(&self, id: Uuid) -> bool {
+        self.subgraphs.read().await.contains_key(&id)
+    }
+
+    /// Number of subgraphs the tornado currently holds
+    pub async fn subgraph_count(&self) -> usize {
+        self.subgraphs.read().await.len()
+    }
+
+    /// Ids of the held subgraphs, in ascending order
+    pub async fn subgraph_ids(&self) -> Vec<Uuid> {
+        let mut ids: Vec<Uuid> = self.subgraphs.read().await.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Call `f` on every held subgraph, in no particular order, under a single read lock
+    /// of the tornado. Sweeping or releasing waits until the walk is done.
+    pub async fn for_each_subgraph<F: FnMut(&Subgraph)>(&self, mut f: F) {
+        let subgraphs = self.subgraphs.read().await;
+        for subgraph in subgraphs.values() {
+            f(&*subgraph.read().await);
+        }
+    }
+
+    /// Release one specific subgraph, if the tornado holds it
+    #[instrument(skip_all, fields(tornado_id = %self.id))]
+    pub async fn release_by_id(&self, id: Uuid) -> Option<Arc<RwLock<Subgraph>>> {
+        let released = self.subgraphs.write().await.remove(&id)?;
+        debug!(subgraph_id = %id, "releasing subgraph");
+        SwarmMetrics::record(&self.metrics.subgraphs_released, 1);
+        Some(released)
+    }
+}
+
+/// Captured state of a single tornado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TornadoSnapshot {
+    pub id: Uuid,
+    pub eye: Vec3,
+    #[serde(default)]
+    pub velocity: Vec3,
+    pub radius: f32,
+    pub angular_velocity: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub merge_on_connect: bool,
+    pub subgraph_ids: Vec<Uuid>,
+}
+
+/// Captured state of a whole swarm, used to pause and resume a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwarmSnapshot {
+    pub tornadoes: Vec<TornadoSnapshot>,
 }
 
 /// Manages multiple tornadoes in the swarm
 #[derive(Debug)]
 pub struct TornadoSwarm {
     pub tornadoes: Arc<RwLock<Vec<Tornado>>>,
+    pub metrics: Arc<SwarmMetrics>,
+    pub max_tornadoes: Option<usize>,
+    /// Limits tornado movement; `None` leaves the world unbounded
+    pub bounds: Option<WorldBounds>,
+    /// Merge tornadoes whose eyes come within the sum of their radii during `simulate_step`
+    pub merge_on_overlap: bool,
+    pub config: SwarmConfig,
 }
 
 impl TornadoSwarm {
     pub fn new() -> Self {
+        Self::with_config(SwarmConfig::default())
+    }
+
+    /// Create a swarm whose tornadoes are spawned with the dynamics in `config`
+    pub fn with_config(config: SwarmConfig) -> Self {
         Self {
             tornadoes: Arc::new(RwLock::new(Vec::new())),
+            metrics: Arc::new(SwarmMetrics::new()),
+            max_tornadoes: None,
+            bounds: None,
+            merge_on_overlap: false,
+            config,
+        }
+    }
+
+    /// Create a swarm that refuses to spawn more than `max` tornadoes
+    pub fn with_max_tornadoes(max: usize) -> Self {
+        Self {
+            max_tornadoes: Some(max),
+            ..Self::new()
+        }
+    }
+
+    /// Keep tornadoes inside `bounds`, reflecting them off its walls
+    pub fn with_bounds(mut self, bounds: WorldBounds) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_merge_on_overlap(mut self, merge_on_overlap: bool) -> Self {
+        self.merge_on_overlap = merge_on_overlap;
+        self
+    }
+
+    /// Move every subgraph held by tornado `source` into tornado `target` and remove `source`
+    /// from the swarm. Subgraphs still waiting in `source`'s intake queue, or swept into it
+    /// later, are delivered to `target` as they drain.
+    pub async fn merge_tornadoes(&self, target: Uuid, source: Uuid) -> Result<(), String> {
+        if target == source {
+            return Err(format!("Cannot merge tornado {} into itself", target));
         }
+        
+        let mut tornadoes = self.tornadoes.write().await;
+        let target_idx = tornadoes.iter().position(|t| t.id == target)
+            .ok_or_else(|| format!("Tornado {} not found", target))?;
+        let source_idx = tornadoes.iter().position(|t| t.id == source)
+            .ok_or_else(|| format!("Tornado {} not found", source))?;
+        
+        let absorbed = tornadoes.remove(source_idx);
+        let target_idx = if source_idx < target_idx { target_idx - 1 } else { target_idx };
+        Self::absorb(&tornadoes[target_idx], absorbed).await;
+        Ok(())
+    }
+
+    /// Hand all of `source`'s subgraphs to `target` and forward anything it receives later
+    async fn absorb(target: &Tornado, source: Tornado) {
+        let moved: Vec<_> = {
+            let mut subgraphs = source.subgraphs.write().await;
+            *source.absorbed_into.lock().unwrap() = Some(target.clone());
+            subgraphs.drain().collect()
+        };
+        info!(target_id = %target.id, source_id = %source.id, subgraphs = moved.len(), "merging tornadoes");
+        target.subgraphs.write().await.extend(moved);
+    }
+
+    /// Current throughput counters across all tornadoes in the swarm
+    pub fn metrics(&self) -> SwarmMetricsSnapshot {
+        self.metrics.snapshot()
     }
 
-    /// Spawn a new tornado at a given position
-    pub async fn spawn_tornado(&self, position: Vec3) {
-        let tornado = Tornado::new(position);
-        println!("{}", format!("🌪️  Spawning new tornado at ({:.1}, {:.1}, {:.1})", 
-            position.x, position.y, position.z
-        ).bright_cyan());
+    /// Spawn a new tornado at a given position, returning its id (None if the swarm is full)
+    pub async fn spawn_tornado(&self, position: Vec3) -> Option<Uuid> {
+        self.spawn_tornado_seeded(position, &mut WingbeatRng::from_entropy()).await
+    }
+
+    /// Spawn a new tornado using a seedable generator for its dynamics
+    pub async fn spawn_tornado_seeded(&self, position: Vec3, rng: &mut WingbeatRng) -> Option<Uuid> {
+        let mut tornadoes = self.tornadoes.write().await;
+        if self.max_tornadoes.is_some_and(|max| tornadoes.len() >= max) {
+            warn!(max_tornadoes = ?self.max_tornadoes, "swarm is full, not spawning tornado");
+            return None;
+        }
         
-        self.tornadoes.write().await.push(tornado);
+        let mut tornado = Tornado::with_config_seeded(position, &self.config, rng);
+        tornado.metrics = Arc::clone(&self.metrics);
+        info!(
+            tornado_id = %tornado.id,
+            x = position.x,
+            y = position.y,
+            z = position.z,
+            "spawning tornado"
+        );
+        
+        let id = tornado.id;
+        tornadoes.push(tornado);
+        Some(id)
     }
 
-    /// Simulate the swarm dynamics
-    pub async fn simulate_step(&self, delta_time: f32) {
+    /// Capture every tornado's dynamics and the ids of the subgraphs it holds
+    pub async fn snapshot(&self) -> SwarmSnapshot {
+        let tornadoes = self.tornadoes.read().await;
+        let mut snapshots = Vec::with_capacity(tornadoes.len());
+        
+        for tornado in tornadoes.iter() {
+            let mut subgraph_ids: Vec<Uuid> = tornado.subgraphs.read().await.keys().cloned().collect();
+            subgraph_ids.sort();
+            
+            snapshots.push(TornadoSnapshot {
+                id: tornado.id,
+                eye: tornado.eye,
+                velocity: tornado.velocity,
+                radius: tornado.radius,
+                angular_velocity: tornado.angular_velocity,
+                height: tornado.height,
+                merge_on_connect: tornado.merge_on_connect,
+                subgraph_ids,
+            });
+        }
+        
+        SwarmSnapshot { tornadoes: snapshots }
+    }
+
+    /// Count every held subgraph's `tornado_strength` into `buckets` equal-width bins over
+    /// [0, 1]; strengths outside the range land in the first or last bin
+    pub async fn strength_histogram(&self, buckets: usize) -> Vec<usize> {
+        let mut histogram = vec![0; buckets];
+        if buckets == 0 {
+            return histogram;
+        }
+        
+        let tornadoes = self.tornadoes.read().await;
+        for tornado in tornadoes.iter() {
+            for subgraph in tornado.subgraphs.read().await.values() {
+                let strength = subgraph.read().await.tornado_strength.clamp(0.0, 1.0);
+                let bucket = ((strength * buckets as f32) as usize).min(buckets - 1);
+                histogram[bucket] += 1;
+            }
+        }
+        
+        histogram
+    }
+
+    /// Number of subgraphs held across all tornadoes. At most `TOTAL_SUBGRAPHS_CONCURRENCY`
+    /// tornado locks are awaited at once, however large the swarm.
+    pub async fn total_subgraphs(&self) -> usize {
         let tornadoes = self.tornadoes.read().await;
+        stream::iter(tornadoes.iter())
+            .map(|tornado| tornado.subgraph_count())
+            .buffer_unordered(TOTAL_SUBGRAPHS_CONCURRENCY)
+            .fold(0, |total, count| async move { total + count })
+            .await
+    }
+
+    /// Rebuild the swarm from a snapshot, reassigning each subgraph to the tornado that held it.
+    /// Subgraphs are taken from the swarm itself or from `released` (e.g. ones released since the
+    /// snapshot, or deserialized after a restart); the swarm is left untouched if any are missing.
+    /// Tornadoes still in the swarm keep their intake queue; ones recreated from scratch have none.
+    pub async fn restore(
+        &self,
+        snapshot: &SwarmSnapshot,
+        released: Vec<Arc<RwLock<Subgraph>>>,
+    ) -> Result<(), String> {
+        let mut tornadoes = self.tornadoes.write().await;
         
+        // Pool every subgraph we know about by id
+        let mut pool: HashMap<Uuid, Arc<RwLock<Subgraph>>> = HashMap::new();
         for tornado in tornadoes.iter() {
-            // Move tornado
-            let mut rng = rand::thread_rng();
-            let _new_position = Vec3::new(
-                tornado.eye.x + rng.gen_range(-1.0..1.0) * delta_time,
-                tornado.eye.y + rng.gen_range(-1.0..1.0) * delta_time,
-                tornado.eye.z,
-            );
+            for (id, subgraph) in tornado.subgraphs.read().await.iter() {
+                pool.insert(*id, Arc::clone(subgraph));
+            }
+        }
+        for subgraph in released {
+            let id = subgraph.read().await.id;
+            pool.insert(id, subgraph);
+        }
+        
+        let mut held = Vec::with_capacity(snapshot.tornadoes.len());
+        for tornado_snapshot in &snapshot.tornadoes {
+            let mut subgraphs = HashMap::new();
+            for id in &tornado_snapshot.subgraph_ids {
+                let subgraph = pool.get(id)
+                    .ok_or_else(|| format!("Subgraph {} not available to restore", id))?;
+                subgraphs.insert(*id, Arc::clone(subgraph));
+            }
+            held.push(subgraphs);
+        }
+        
+        let mut restored = Vec::with_capacity(snapshot.tornadoes.len());
+        for (tornado_snapshot, subgraphs) in snapshot.tornadoes.iter().zip(held) {
+            // A tornado that is still live keeps its map and intake queue, so its drain task
+            // goes on delivering into the restored tornado
+            let (map, intake, absorbed_into) = match tornadoes.iter().find(|t| t.id == tornado_snapshot.id) {
+                Some(existing) => {
+                    *existing.subgraphs.write().await = subgraphs;
+                    (Arc::clone(&existing.subgraphs), existing.intake.clone(), Arc::clone(&existing.absorbed_into))
+                }
+                None => (Arc::new(RwLock::new(subgraphs)), None, Arc::default()),
+            };
             
+            restored.push(Tornado {
+                id: tornado_snapshot.id,
+                eye: tornado_snapshot.eye,
+                velocity: tornado_snapshot.velocity,
+                radius: tornado_snapshot.radius,
+                angular_velocity: tornado_snapshot.angular_velocity,
+                height: tornado_snapshot.height,
+                subgraphs: map,
+                metrics: Arc::clone(&self.metrics),
+                intake,
+                merge_on_connect: tornado_snapshot.merge_on_connect,
+                absorbed_into,
+            });
+        }
+        
+        *tornadoes = restored;
+        Ok(())
+    }
+
+    /// Simulate the swarm dynamics
+    pub async fn simulate_step(&self, delta_time: f32) {
+        self.simulate_step_seeded(delta_time, &mut WingbeatRng::from_entropy()).await
+    }
+
+    /// Simulate the swarm dynamics, drawing each tornado's wander from a seedable generator
+    pub async fn simulate_step_seeded(&self, delta_time: f32, rng: &mut WingbeatRng) {
+        {
+            let mut tornadoes = self.tornadoes.write().await;
+            
+            for tornado in tornadoes.iter_mut() {
+                // Drift with the tornado's velocity plus a random wander
+                let wander = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0) * self.config.drift;
+                let position = tornado.eye + (tornado.velocity + wander) * delta_time;
+                
+                match self.bounds {
+                    Some(bounds) => {
+                        (tornado.eye, tornado.velocity) = bounds.reflect(position, tornado.velocity);
+                    }
+                    None => tornado.eye = position,
+                }
+            }
+            
+            if self.merge_on_overlap {
+                // Each tornado absorbs every later one it overlaps, so the earliest spawned survives
+                let mut i = 0;
+                while i < tornadoes.len() {
+                    let mut j = i + 1;
+                    while j < tornadoes.len() {
+                        let (a, b) = (&tornadoes[i], &tornadoes[j]);
+                        if a.eye.distance(&b.eye) < a.radius + b.radius {
+                            let absorbed = tornadoes.remove(j);
+                            Self::absorb(&tornadoes[i], absorbed).await;
+                        } else {
+                            j += 1;
+                        }
+                    }
+                    i += 1;
+                }
+            }
+        }
+        
+        let tornadoes = self.tornadoes.read().await;
+        for tornado in tornadoes.iter() {
             // Spin tornado
             tornado.spin().await;
         }
     }
+}
+
+impl Default for TornadoSwarm {
+    fn default() -> Self {
+        Self::new()
+    }
 } 
\ No newline at end of file
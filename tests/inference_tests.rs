@@ -0,0 +1,716 @@
+use wingbeat::{Tensor, TensorOps, WingbeatError, ModelWeights, LayerFactory, LayerOperation, LayerContext, LayerType, InferenceEngine, Tokenizer, WingbeatRng, TransformerConfig, Activation, BatchConfig, ModelBundle};
+use wingbeat::inference::{BPETokenizer, NormalizerConfig, SimpleTokenizer, Token, TokenizerKind};
+use wingbeat::inference::similarity::{cosine, NearestNeighbors};
+use wingbeat::inference::layer_ops::{apply_attention_mask, AttentionLayer, Dropout, LayerResult, PoolKind, PoolingLayer};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+#[test]
+fn test_shape_mismatch_error() {
+    let a = Tensor::zeros(vec![2, 3]);
+    let b = Tensor::zeros(vec![3, 2]);
+    
+    match a.add(&b) {
+        Err(WingbeatError::ShapeMismatch { expected, got }) => {
+            assert_eq!(expected, vec![2, 3]);
+            assert_eq!(got, vec![3, 2]);
+        }
+        other => panic!("expected ShapeMismatch, got {:?}", other),
+    }
+    
+    let err = a.matmul(&a).unwrap_err();
+    assert_eq!(err.to_string(), "Shape mismatch: expected [3, 3], got [2, 3]");
+}
+
+#[test]
+fn test_parameter_not_found_error() {
+    let weights = ModelWeights::new();
+    let layer = LayerFactory::create_layer(LayerType::Embedding, Uuid::new_v4(), HashMap::new());
+    let context = LayerContext {
+        input: Tensor::new(vec![1, 1], vec![0.0]),
+        output: None,
+        attention_mask: None,
+        metadata: HashMap::new(),
+    };
+    
+    let err = layer.execute(context, &weights).unwrap_err();
+    assert!(matches!(err, WingbeatError::ParameterNotFound(ref name) if name == "embedding.weight"));
+}
+
+#[test]
+fn test_attention_mask_zeroes_masked_keys() {
+    let scores = Tensor::new(vec![2, 3], vec![0.5, 1.0, 3.0, 2.0, 0.1, 4.0]);
+    let mask = Tensor::new(vec![1, 3], vec![1.0, 1.0, 0.0]);
+    
+    let probs = apply_attention_mask(&scores, &mask).unwrap().softmax();
+    assert_eq!(probs.data[2], 0.0);
+    assert_eq!(probs.data[5], 0.0);
+    assert!(probs.data.iter().all(|p| p.is_finite()));
+    
+    // Mask shape must broadcast or match the scores
+    let bad_mask = Tensor::new(vec![2, 2], vec![1.0; 4]);
+    assert!(apply_attention_mask(&scores, &bad_mask).is_err());
+}
+
+#[test]
+fn test_attention_layer_with_mask() {
+    let layer_id = Uuid::new_v4();
+    let mut weights = ModelWeights::new();
+    weights.init_transformer_layer(layer_id, 4, 10);
+    
+    let config = HashMap::from([("hidden_size".to_string(), 4), ("num_heads".to_string(), 2)]);
+    let layer = LayerFactory::create_layer(LayerType::Attention, layer_id, config);
+    let context = LayerContext {
+        input: Tensor::random(vec![3, 4]),
+        output: None,
+        attention_mask: Some(Tensor::new(vec![1, 3], vec![1.0, 1.0, 0.0])),
+        metadata: HashMap::new(),
+    };
+    
+    let result = layer.execute(context, &weights).unwrap();
+    assert_eq!(result.output.shape, vec![3, 4]);
+    assert!(result.output.data.iter().all(|x| x.is_finite()));
+}
+
+#[test]
+fn test_forward_hooks_fire_in_order() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer(2, 8, 16);
+    let num_layers = engine.layer_operations.len();
+    
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    for layer_index in 0..num_layers {
+        let calls = Arc::clone(&calls);
+        engine.register_hook(layer_index, Box::new(move |output: &Tensor| {
+            assert!(!output.data.is_empty());
+            calls.lock().unwrap().push(layer_index);
+        }));
+    }
+    
+    engine.infer("hello swarm").unwrap();
+    
+    assert_eq!(*calls.lock().unwrap(), (0..num_layers).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_seeded_tensors_are_identical() {
+    let a = Tensor::random_seeded(vec![4, 8], &mut WingbeatRng::seed_from_u64(42));
+    let b = Tensor::random_seeded(vec![4, 8], &mut WingbeatRng::seed_from_u64(42));
+    let c = Tensor::random_seeded(vec![4, 8], &mut WingbeatRng::seed_from_u64(7));
+    
+    assert_eq!(a.shape, b.shape);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+}
+
+#[test]
+fn test_seeded_engines_have_identical_weights() {
+    let mut first = InferenceEngine::new("simple");
+    first.init_transformer_seeded(2, 8, 16, &mut WingbeatRng::seed_from_u64(3));
+    let mut second = InferenceEngine::new("simple");
+    second.init_transformer_seeded(2, 8, 16, &mut WingbeatRng::seed_from_u64(3));
+    
+    for (name, param) in &first.weights.parameters {
+        let other = second.weights.get_parameter(name).unwrap();
+        assert_eq!(param.tensor.data, other.tensor.data, "parameter {} differs", name);
+    }
+    assert_eq!(first.infer("hello swarm").unwrap(), second.infer("hello swarm").unwrap());
+}
+
+#[test]
+fn test_config_controls_attention_heads() {
+    let cfg = TransformerConfig::new(2, 16, 100)
+        .with_num_heads(8)
+        .with_intermediate_size(32)
+        .with_activation(Activation::Gelu);
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_from_config_seeded(&cfg, &mut WingbeatRng::seed_from_u64(3));
+    
+    let ffn_weight = engine.weights.get_parameter("ffn.intermediate.weight").unwrap();
+    assert_eq!(ffn_weight.tensor.shape, vec![16, 32]);
+    
+    let attention_layers: Vec<_> = engine.layer_operations.iter()
+        .filter(|op| matches!(op.layer_type(), LayerType::Attention))
+        .collect();
+    assert_eq!(attention_layers.len(), 2);
+    
+    for layer in attention_layers {
+        let context = LayerContext {
+            input: Tensor::ones(vec![3, 16]),
+            output: None,
+            attention_mask: None,
+            metadata: HashMap::new(),
+        };
+        let result = layer.execute(context, &engine.weights).unwrap();
+        assert_eq!(result.metadata["num_heads"], "8");
+    }
+    
+    // Head count and norm epsilon both change what the layer computes
+    let layer_id = Uuid::new_v4();
+    let input = Tensor::random_seeded(vec![3, 16], &mut WingbeatRng::seed_from_u64(4));
+    let attend = |cfg: &TransformerConfig| {
+        LayerFactory::from_config(LayerType::Attention, layer_id, cfg).forward(&input, &engine.weights)
+    };
+    let eight_heads = attend(&cfg).unwrap();
+    assert_ne!(eight_heads.data, attend(&cfg.clone().with_num_heads(1)).unwrap().data);
+    assert_ne!(eight_heads.data, attend(&cfg.clone().with_norm_eps(10.0)).unwrap().data);
+    assert!(attend(&cfg.clone().with_num_heads(5)).is_err());
+    
+    let feedforward = |cfg: &TransformerConfig| {
+        LayerFactory::from_config(LayerType::FeedForward, layer_id, cfg).forward(&input, &engine.weights).unwrap()
+    };
+    assert_ne!(feedforward(&cfg).data, feedforward(&cfg.clone().with_norm_eps(10.0)).data);
+}
+
+#[test]
+fn test_forward_matches_execute() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_from_config_seeded(&TransformerConfig::new(2, 8, 20).with_use_bias(true), &mut WingbeatRng::seed_from_u64(11));
+    let tokens = Tensor::new(vec![3], vec![4.0, 7.0, 2.0]);
+    
+    let mut input = tokens;
+    for layer in &engine.layer_operations {
+        let context = LayerContext { input: input.clone(), output: None, attention_mask: None, metadata: HashMap::new() };
+        let executed = layer.execute(context, &engine.weights).unwrap();
+        let forwarded = layer.forward(&input, &engine.weights).unwrap();
+        assert_eq!(forwarded, executed.output);
+        input = forwarded;
+    }
+}
+
+#[test]
+fn test_default_execute_uses_forward() {
+    #[derive(Debug)]
+    struct Doubling;
+    
+    impl LayerOperation for Doubling {
+        fn forward(&self, input: &Tensor, _weights: &ModelWeights) -> Result<Tensor, WingbeatError> {
+            let mut output = input.clone();
+            output.mul_scalar_(2.0);
+            Ok(output)
+        }
+        
+        fn layer_type(&self) -> LayerType {
+            LayerType::Custom("doubling".to_string())
+        }
+        
+        fn layer_id(&self) -> Uuid {
+            Uuid::nil()
+        }
+    }
+    
+    let context = LayerContext { input: Tensor::new(vec![2], vec![1.0, -3.0]), output: None, attention_mask: None, metadata: HashMap::new() };
+    let result = Doubling.execute(context, &ModelWeights::new()).unwrap();
+    assert_eq!(result.output.data, vec![2.0, -6.0]);
+    assert!(result.metadata.is_empty());
+}
+
+#[test]
+fn test_stats_report_configured_layer_count() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_from_config_seeded(&TransformerConfig::new(6, 8, 20), &mut WingbeatRng::seed_from_u64(4));
+    
+    let stats = engine.get_stats();
+    assert_eq!(engine.num_layers(), 6);
+    assert_eq!(stats["num_layers"], "6");
+    assert_eq!(stats["parameters.embedding"], (20 * 8).to_string());
+    assert_eq!(stats["parameters.attention"], (4 * 8 * 8).to_string());
+    assert_eq!(stats["parameters.ffn"], (2 * 8 * 32).to_string());
+    
+    let breakdown_total: usize = engine.weights.parameter_breakdown().values().sum();
+    assert_eq!(stats["total_parameters"], breakdown_total.to_string());
+}
+
+#[test]
+fn test_untied_embeddings_allocate_output_weight() {
+    let mut rng = WingbeatRng::seed_from_u64(5);
+    let mut tied = InferenceEngine::new("simple");
+    tied.init_from_config_seeded(&TransformerConfig::new(1, 8, 20), &mut rng);
+    assert!(tied.weights.get_parameter("output.weight").is_none());
+    
+    let mut untied = InferenceEngine::new("simple");
+    untied.init_from_config_seeded(&TransformerConfig::new(1, 8, 20).with_tie_embeddings(false), &mut rng);
+    let output_weight = untied.weights.get_parameter("output.weight").unwrap();
+    assert_eq!(output_weight.tensor.shape, vec![20, 8]);
+    assert_ne!(output_weight.tensor.data, untied.weights.get_parameter("embedding.weight").unwrap().tensor.data);
+    
+    let output_layer = untied.layer_operations.last().unwrap();
+    let context = LayerContext {
+        input: Tensor::ones(vec![2, 8]),
+        output: None,
+        attention_mask: None,
+        metadata: HashMap::new(),
+    };
+    let result = output_layer.execute(context, &untied.weights).unwrap();
+    assert_eq!(result.output.shape, vec![2, 20]);
+    assert_eq!(result.metadata["tie_embeddings"], "false");
+}
+
+#[test]
+fn test_attention_dropout_only_in_training() {
+    let layer_id = Uuid::new_v4();
+    let mut weights = ModelWeights::new();
+    weights.init_transformer_layer_seeded(layer_id, 4, 10, &mut WingbeatRng::seed_from_u64(22));
+    
+    let layer = AttentionLayer {
+        layer_id,
+        hidden_size: 4,
+        num_heads: 1,
+        norm_eps: 1e-5,
+        dropout: Some(Dropout::new(1.0, WingbeatRng::seed_from_u64(23))),
+    };
+    let plain = AttentionLayer { layer_id, hidden_size: 4, num_heads: 1, norm_eps: 1e-5, dropout: None };
+    let context = |train: bool| LayerContext {
+        input: Tensor::ones(vec![2, 4]),
+        output: None,
+        attention_mask: None,
+        metadata: HashMap::from([("train".to_string(), train.to_string())]),
+    };
+    
+    let eval = layer.execute(context(false), &weights).unwrap();
+    assert_eq!(eval.output.data, plain.execute(context(false), &weights).unwrap().output.data);
+    
+    let train = layer.execute(context(true), &weights).unwrap();
+    assert!(train.output.data.iter().all(|&x| x == 0.0));
+}
+
+#[test]
+fn test_uniform_model_perplexity_is_vocab_size() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_from_config_seeded(&TransformerConfig::new(1, 8, 50), &mut WingbeatRng::seed_from_u64(24));
+    
+    // Zero weights give identical logits for every token
+    for param in Arc::make_mut(&mut engine.weights).parameters.values_mut() {
+        param.tensor = Arc::new(Tensor::zeros(param.tensor.shape.clone()));
+    }
+    
+    let perplexity = engine.perplexity("the swarm lifts every wing").unwrap();
+    assert!((perplexity - 50.0).abs() < 1e-2, "perplexity {}", perplexity);
+    
+    assert!(engine.perplexity("alone").is_err());
+}
+
+#[test]
+fn test_engine_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<InferenceEngine>();
+    assert_send_sync::<Box<dyn LayerOperation>>();
+    assert_send_sync::<Box<dyn wingbeat::Tokenizer>>();
+    
+    // The engine can move into a spawned thread and run there
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(1, 8, 20, &mut WingbeatRng::seed_from_u64(31));
+    let output = std::thread::spawn(move || engine.infer("over the wire")).join().unwrap();
+    assert!(output.is_ok());
+}
+
+#[test]
+fn test_cloned_engines_share_weights() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(1, 8, 40, &mut WingbeatRng::seed_from_u64(32));
+    let replica = engine.clone();
+    
+    assert_eq!(replica.infer("twin wings").unwrap(), engine.infer("twin wings").unwrap());
+    
+    // One copy of the weights, referenced by both engines
+    assert!(Arc::ptr_eq(&engine.weights, &replica.weights));
+    assert_eq!(Arc::strong_count(&engine.weights), 2);
+    
+    // Re-initializing one engine copies its weights instead of changing the replica
+    let before = replica.weights.get_parameter("embedding.weight").unwrap().tensor.data.clone();
+    engine.init_transformer_seeded(1, 8, 40, &mut WingbeatRng::seed_from_u64(33));
+    assert!(!Arc::ptr_eq(&engine.weights, &replica.weights));
+    assert_eq!(replica.weights.get_parameter("embedding.weight").unwrap().tensor.data, before);
+    assert_ne!(engine.weights.get_parameter("embedding.weight").unwrap().tensor.data, before);
+}
+
+#[tokio::test]
+async fn test_shared_parameter_is_not_copied() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(1, 64, 500, &mut WingbeatRng::seed_from_u64(34));
+    let buffer = engine.weights.get_parameter("embedding.weight").unwrap().tensor.data.as_ptr();
+    
+    // Two tasks each multiply through their own handle to the same buffer
+    let mut handles = Vec::new();
+    for _ in 0..2 {
+        let weight = engine.weights.share_parameter("embedding.weight").unwrap();
+        handles.push(tokio::spawn(async move {
+            let input = Tensor::ones(vec![1, weight.shape[0]]);
+            let output = input.matmul(&weight).unwrap();
+            (weight.data.as_ptr() as usize, output)
+        }));
+    }
+    
+    for handle in handles {
+        let (address, output) = handle.await.unwrap();
+        assert_eq!(address, buffer as usize);
+        assert_eq!(output.shape, vec![1, 64]);
+    }
+    assert_eq!(Arc::strong_count(&engine.weights.get_parameter("embedding.weight").unwrap().tensor), 1);
+    assert!(engine.weights.share_parameter("missing").is_none());
+}
+
+#[test]
+fn test_decode_output_takes_argmax_per_position() {
+    let engine = InferenceEngine::new("simple");
+    let vocab = engine.tokenizer.vocab_size();
+    
+    let mut logits = Tensor::zeros(vec![3, vocab + 2]);
+    for (position, &token) in [3usize, 0, 2].iter().enumerate() {
+        logits.data[position * (vocab + 2) + token] = 4.0;
+    }
+    // A logit beyond the vocabulary never wins
+    logits.data[vocab] = 9.0;
+    
+    assert_eq!(engine.decode_output(&logits).unwrap(), "<eos> <pad> <sos>");
+    assert!(engine.decode_output(&Tensor::zeros(vec![2, 0])).is_err());
+}
+
+#[test]
+fn test_empty_input_is_rejected() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(1, 8, 20, &mut WingbeatRng::seed_from_u64(35));
+    
+    for text in ["", "   "] {
+        assert!(matches!(engine.infer(text), Err(WingbeatError::EmptyInput)));
+        assert!(matches!(engine.perplexity(text), Err(WingbeatError::EmptyInput)));
+    }
+}
+
+#[test]
+fn test_simple_tokenizer_offsets_index_source_text() {
+    let text = "héllo  wörld\tend";
+    let tokens = wingbeat::inference::SimpleTokenizer::new().encode(text).unwrap();
+    
+    let slices: Vec<&str> = tokens.iter().map(|t| &text[t.start..t.end]).collect();
+    assert_eq!(slices, vec!["héllo", "wörld", "end"]);
+}
+
+#[test]
+fn test_infer_profiled_times_every_layer() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(2, 16, 100, &mut WingbeatRng::seed_from_u64(11));
+    
+    let (output, timings) = engine.infer_profiled("profile each layer").unwrap();
+    
+    assert_eq!(output, engine.infer("profile each layer").unwrap());
+    assert_eq!(timings.len(), engine.layer_operations.len());
+    for (i, timing) in timings.iter().enumerate() {
+        assert_eq!(timing.layer_index, i);
+    }
+    assert!(matches!(timings[0].layer_type, LayerType::Embedding));
+    assert!(matches!(timings.last().unwrap().layer_type, LayerType::Output));
+}
+
+#[test]
+fn test_feedforward_biases() {
+    let layer_id = Uuid::new_v4();
+    let cfg = TransformerConfig::new(1, 4, 10).with_use_bias(true);
+    let mut weights = ModelWeights::new();
+    weights.init_layer_from_config(layer_id, &cfg, &mut WingbeatRng::seed_from_u64(5));
+    let unbiased = ModelWeights {
+        parameters: weights.parameters.iter()
+            .filter(|(name, _)| !name.ends_with(".bias"))
+            .map(|(name, param)| (name.clone(), param.clone()))
+            .collect(),
+        layer_parameters: HashMap::new(),
+    };
+    
+    let layer = LayerFactory::from_config(LayerType::FeedForward, layer_id, &cfg);
+    let input = Tensor::random_seeded(vec![2, 4], &mut WingbeatRng::seed_from_u64(6));
+    let run = |weights: &ModelWeights| {
+        let context = LayerContext {
+            input: input.clone(),
+            output: None,
+            attention_mask: None,
+            metadata: HashMap::new(),
+        };
+        layer.execute(context, weights).unwrap().output
+    };
+    
+    // Zero biases are a no-op
+    let baseline = run(&unbiased);
+    assert_eq!(run(&weights).data, baseline.data);
+    
+    // An output bias shifts every row by the bias vector
+    let bias = Tensor::new(vec![4], vec![1.0, -2.0, 0.5, 3.0]);
+    weights.add_parameter("ffn.output.bias".to_string(), bias.clone(), layer_id);
+    let shifted = run(&weights);
+    for (i, (&got, &base)) in shifted.data.iter().zip(&baseline.data).enumerate() {
+        assert!((got - (base + bias.data[i % 4])).abs() < 1e-5);
+    }
+    
+    // A bias of the wrong width is rejected
+    weights.add_parameter("ffn.output.bias".to_string(), Tensor::zeros(vec![3]), layer_id);
+    let context = LayerContext { input, output: None, attention_mask: None, metadata: HashMap::new() };
+    assert!(matches!(layer.execute(context, &weights), Err(WingbeatError::ShapeMismatch { .. })));
+}
+
+#[test]
+fn test_wrongly_shaped_weight_is_rejected() {
+    let layer_id = Uuid::new_v4();
+    let mut weights = ModelWeights::new();
+    weights.init_transformer_layer(layer_id, 4, 10);
+    
+    // A [vocab, hidden] table stored transposed
+    weights.add_parameter("embedding.weight".to_string(), Tensor::zeros(vec![4, 10]), layer_id);
+    let param = weights.get_parameter("embedding.weight").unwrap();
+    assert!(param.assert_shape(&[4, 10]).is_ok());
+    
+    let config = HashMap::from([("hidden_size".to_string(), 4), ("vocab_size".to_string(), 10)]);
+    let layer = LayerFactory::create_layer(LayerType::Embedding, layer_id, config);
+    let context = LayerContext {
+        input: Tensor::new(vec![1, 2], vec![1.0, 2.0]),
+        output: None,
+        attention_mask: None,
+        metadata: HashMap::new(),
+    };
+    
+    let err = layer.execute(context, &weights).unwrap_err();
+    assert!(matches!(err, WingbeatError::ShapeMismatch { ref expected, ref got } if *expected == [10, 4] && *got == [4, 10]));
+}
+
+/// Passes its input through unchanged
+#[derive(Debug)]
+struct IdentityLayer {
+    layer_id: Uuid,
+}
+
+impl LayerOperation for IdentityLayer {
+    fn forward(&self, input: &Tensor, _weights: &ModelWeights) -> Result<Tensor, WingbeatError> {
+        Ok(input.clone())
+    }
+
+    fn execute(&self, context: LayerContext, _weights: &ModelWeights) -> Result<LayerResult, WingbeatError> {
+        Ok(LayerResult {
+            output: context.input,
+            metadata: HashMap::from([("operation".to_string(), "identity".to_string())]),
+        })
+    }
+
+    fn layer_type(&self) -> LayerType {
+        LayerType::Custom("identity".to_string())
+    }
+
+    fn layer_id(&self) -> Uuid {
+        self.layer_id
+    }
+}
+
+#[test]
+fn test_registered_custom_layer() {
+    LayerFactory::register("identity", Box::new(|layer_id, _config| Box::new(IdentityLayer { layer_id })));
+    
+    let layer_id = Uuid::new_v4();
+    let layer = LayerFactory::create_layer(LayerType::Custom("identity".to_string()), layer_id, HashMap::new());
+    assert_eq!(layer.layer_id(), layer_id);
+    assert!(matches!(layer.layer_type(), LayerType::Custom(ref name) if name == "identity"));
+    
+    let input = Tensor::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]);
+    let context = LayerContext { input: input.clone(), output: None, attention_mask: None, metadata: HashMap::new() };
+    let result = layer.execute(context, &ModelWeights::new()).unwrap();
+    assert_eq!(result.output.data, input.data);
+    assert_eq!(result.metadata["operation"], "identity");
+}
+
+#[test]
+fn test_pooling_layer() {
+    let input = Tensor::new(vec![2, 3], vec![1.0, 4.0, -2.0, 3.0, 0.0, 6.0]);
+    let pool = |kind| {
+        let context = LayerContext { input: input.clone(), output: None, attention_mask: None, metadata: HashMap::new() };
+        PoolingLayer::new(Uuid::new_v4(), kind).execute(context, &ModelWeights::new()).unwrap().output
+    };
+    
+    let mean = pool(PoolKind::Mean);
+    assert_eq!(mean.shape, vec![1, 3]);
+    assert_eq!(mean.data, vec![2.0, 2.0, 2.0]);
+    assert_eq!(pool(PoolKind::Max).data, vec![3.0, 4.0, 6.0]);
+    assert_eq!(pool(PoolKind::Cls).data, vec![1.0, 4.0, -2.0]);
+    
+    let layer = LayerFactory::create_layer(LayerType::Pooling, Uuid::new_v4(), HashMap::new());
+    assert!(matches!(layer.layer_type(), LayerType::Pooling));
+}
+
+#[test]
+fn test_embed_is_deterministic_per_input() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(2, 16, 100, &mut WingbeatRng::seed_from_u64(12));
+    
+    let a = engine.embed("the same words").unwrap();
+    let b = engine.embed("the same words").unwrap();
+    let c = engine.embed("<eos> different words").unwrap();
+    
+    assert_eq!(a.shape, vec![1, 16]);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert!(matches!(engine.embed("  "), Err(WingbeatError::EmptyInput)));
+}
+
+#[test]
+fn test_embedding_is_its_own_nearest_neighbor() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(1, 16, 100, &mut WingbeatRng::seed_from_u64(13));
+    
+    let mut index = NearestNeighbors::new();
+    for text in ["<sos> hello", "<eos> <eos> bye", "<pad> something else"] {
+        index.insert(text, engine.embed(text).unwrap());
+    }
+    
+    let query = engine.embed("<eos> <eos> bye").unwrap();
+    let top = index.top_k(&query, 2).unwrap();
+    assert_eq!(top.len(), 2);
+    assert_eq!(top[0].0, "<eos> <eos> bye");
+    assert!((top[0].1 - 1.0).abs() < 1e-5);
+    assert!(top[0].1 >= top[1].1);
+    
+    // Zero vectors have no direction and mismatched sizes are rejected
+    assert_eq!(cosine(&query, &Tensor::zeros(vec![1, 16])).unwrap(), 0.0);
+    assert!(cosine(&query, &Tensor::zeros(vec![1, 8])).is_err());
+}
+
+#[test]
+fn test_infer_batch_matches_individual_runs() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(2, 16, 100, &mut WingbeatRng::seed_from_u64(14));
+    
+    let texts = ["<sos> a short one <eos>", "<eos>", "three <pad> words", "<sos> <sos> <eos> <eos> <sos> longest of all"];
+    let individual: Vec<String> = texts.iter().map(|t| engine.infer(t).unwrap()).collect();
+    
+    assert_eq!(engine.infer_batch(&texts, &BatchConfig::default()).unwrap(), individual);
+    
+    // Splitting into several forward passes gives the same results
+    let small = BatchConfig::new().with_max_batch_size(3).with_pad_token_id(3);
+    assert_eq!(engine.infer_batch(&texts, &small).unwrap(), individual);
+    assert!(engine.infer_batch(&[], &small).unwrap().is_empty());
+}
+
+#[test]
+fn test_embedding_rejects_out_of_range_token() {
+    let layer_id = Uuid::new_v4();
+    let mut weights = ModelWeights::new();
+    weights.init_transformer_layer(layer_id, 4, 10);
+    
+    let config = HashMap::from([("hidden_size".to_string(), 4), ("vocab_size".to_string(), 10)]);
+    let layer = LayerFactory::create_layer(LayerType::Embedding, layer_id, config);
+    let context = |ids: Vec<f32>| LayerContext {
+        input: Tensor::new(vec![1, ids.len()], ids),
+        output: None,
+        attention_mask: None,
+        metadata: HashMap::new(),
+    };
+    
+    assert_eq!(layer.execute(context(vec![0.0, 9.0]), &weights).unwrap().output.shape, vec![2, 4]);
+    let err = layer.execute(context(vec![3.0, 12.0]), &weights).unwrap_err();
+    assert!(matches!(err, WingbeatError::TokenOutOfRange { id: 12, vocab_size: 10 }));
+    
+    // Ids that would silently truncate to a valid row are rejected before the lookup
+    for bad in [-1.0, 2.5, f32::NAN, f32::INFINITY] {
+        let err = layer.execute(context(vec![0.0, bad]), &weights).unwrap_err();
+        assert!(matches!(err, WingbeatError::InvalidTokenId(_)));
+    }
+}
+
+#[test]
+fn test_model_bundle_round_trip() {
+    let cfg = TransformerConfig::new(2, 8, 32).with_tie_embeddings(false);
+    let mut tokenizer = SimpleTokenizer::new();
+    tokenizer.build_from_text("the swarm carries the model the swarm", 32);
+    
+    let mut engine = InferenceEngine::new("simple");
+    engine.tokenizer = Arc::new(tokenizer.clone());
+    engine.init_from_config_seeded(&cfg, &mut WingbeatRng::seed_from_u64(15));
+    let expected = engine.infer("the swarm carries the model").unwrap();
+    
+    let dir = std::env::temp_dir().join(format!("wingbeat-bundle-{}", Uuid::new_v4()));
+    let bundle = ModelBundle::new(cfg.clone(), (*engine.weights).clone(), TokenizerKind::Simple(tokenizer));
+    bundle.save_dir(&dir).unwrap();
+    for file in ["config.json", "weights.safetensors", "tokenizer.json"] {
+        assert!(dir.join(file).exists());
+    }
+    
+    let loaded = ModelBundle::load_dir(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(loaded.config, cfg);
+    assert_eq!(loaded.weights.parameter_count(), engine.weights.parameter_count());
+    
+    let restored = loaded.into_engine();
+    assert_eq!(restored.layer_operations.len(), engine.layer_operations.len());
+    assert_eq!(restored.infer("the swarm carries the model").unwrap(), expected);
+}
+
+#[test]
+fn test_tokenizer_normalization() {
+    let normalizer = NormalizerConfig::new().with_lowercase(true);
+    let mut tokenizer = SimpleTokenizer::new().with_normalizer(normalizer);
+    tokenizer.build_from_text("Hello hello HELLO world", 10);
+    
+    let tokens = tokenizer.encode("Hello hello").unwrap();
+    assert_eq!(tokens[0].id, tokens[1].id);
+    assert_ne!(tokens[0].id, 1);
+    assert_eq!(tokenizer.vocab_size(), 6);
+    
+    // Accent stripping and NFKC let "Café" and "cafe" collide
+    let folding = normalizer.with_nfkc(true).with_strip_accents(true);
+    assert_eq!(folding.normalize("Café"), "cafe");
+    assert_eq!(folding.normalize("ﬁne"), "fine");
+    assert_eq!(NormalizerConfig::default().normalize("Café"), "Café");
+}
+
+#[test]
+fn test_vocab_min_count() {
+    let mut tokenizer = SimpleTokenizer::new();
+    tokenizer.build_from_text_with_min_count("swarm swarm swarm tornado tornado singleton", 100, 2);
+    
+    assert!(tokenizer.vocab.contains_key("swarm"));
+    assert!(tokenizer.vocab.contains_key("tornado"));
+    assert!(!tokenizer.vocab.contains_key("singleton"));
+    assert_eq!(tokenizer.encode("singleton").unwrap()[0].id, 1);
+    
+    // Frequency order is kept for the words that pass
+    assert!(tokenizer.vocab["swarm"] < tokenizer.vocab["tornado"]);
+}
+
+#[test]
+fn test_reverse_vocab_is_indexed_by_id() {
+    let mut simple = SimpleTokenizer::new();
+    simple.build_from_text("wind over wind and water", 100);
+    let mut bpe = BPETokenizer::new();
+    bpe.train("wind over water", 0);
+    
+    for (vocab, reverse_vocab) in [(&simple.vocab, &simple.reverse_vocab), (&bpe.vocab, &bpe.reverse_vocab)] {
+        assert_eq!(reverse_vocab.len(), vocab.len());
+        for (text, &id) in vocab {
+            assert_eq!(&reverse_vocab[id as usize], text);
+        }
+    }
+    
+    // Decoding reads the vocabulary by id, not the tokens' own text
+    let garble = |mut tokens: Vec<Token>| {
+        for token in &mut tokens {
+            token.text = "?".to_string();
+        }
+        tokens
+    };
+    let text = "wind and unknown water";
+    assert_eq!(simple.decode(&garble(simple.encode(text).unwrap())).unwrap(), "wind and <unk> water");
+    assert_eq!(bpe.decode(&garble(bpe.encode("window").unwrap())).unwrap(), "window");
+    
+    let stray = Token { id: simple.vocab_size as u32, text: "wind".to_string(), start: 0, end: 4 };
+    assert!(matches!(simple.decode(&[stray]), Err(WingbeatError::TokenOutOfRange { .. })));
+}
+
+#[test]
+fn test_decode_with_offsets_restores_spacing() {
+    let tokenizer = SimpleTokenizer::new();
+    
+    for text in ["hi   there", "  leading and trailing", "one"] {
+        let tokens = tokenizer.encode(text).unwrap();
+        assert_eq!(tokenizer.decode_with_offsets(&tokens), text.trim_end());
+    }
+    
+    // Plain `decode` joins vocabulary entries with single spaces
+    let mut tokenizer = SimpleTokenizer::new();
+    tokenizer.build_from_text("hi there", 100);
+    assert_eq!(tokenizer.decode(&tokenizer.encode("hi   there").unwrap()).unwrap(), "hi there");
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "prometheus")]
+
+use wingbeat::metrics::render_prometheus;
+use wingbeat::{InferenceEngine, Subgraph, TornadoSwarm, Vec3, WingbeatRng};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[tokio::test]
+async fn test_prometheus_exposition_names() {
+    let mut rng = WingbeatRng::seed_from_u64(13);
+    let swarm = TornadoSwarm::new();
+    swarm.spawn_tornado_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng).await;
+    swarm.tornadoes.read().await[0]
+        .sweep_up(Arc::new(RwLock::new(Subgraph::new_seeded(&mut rng))))
+        .await;
+    
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(1, 8, 1000, &mut rng);
+    engine.infer("hello world").unwrap();
+    
+    let text = render_prometheus(&swarm.metrics(), &engine.metrics.snapshot());
+    for name in [
+        "wingbeat_subgraphs_swept_total 1",
+        "wingbeat_subgraphs_released_total 0",
+        "wingbeat_spins_total",
+        "wingbeat_connections_detected_total",
+        "wingbeat_forward_passes_total 1",
+        "wingbeat_tokens_generated_total",
+        "# TYPE wingbeat_avg_forward_ms gauge",
+    ] {
+        assert!(text.contains(name), "missing {} in:\n{}", name, text);
+    }
+}
@@ -0,0 +1,19 @@
+use colored::Colorize;
+use wingbeat::{OutputMode, output_mode, set_output_mode};
+use wingbeat::output::render;
+
+// Output mode is process-wide, so every check lives in one test
+#[test]
+fn test_output_modes() {
+    set_output_mode(OutputMode::Color);
+    assert_eq!(output_mode(), OutputMode::Color);
+    assert!(render("swarm".bright_cyan().bold()).unwrap().contains('\x1b'));
+    
+    set_output_mode(OutputMode::Plain);
+    let plain = render("🌪️ swarm".bright_cyan().bold()).unwrap();
+    assert_eq!(plain, "🌪️ swarm");
+    assert!(!plain.contains('\x1b'));
+    
+    set_output_mode(OutputMode::Quiet);
+    assert_eq!(render("swarm".green()), None);
+}
@@ -1,5 +1,11 @@
-use wingbeat::{Subgraph, TornadoSwarm, Vec3, PromptProcessor};
+use wingbeat::{Subgraph, SubgraphType, CompatibilityRules, Tornado, TornadoSwarm, Vec3, WorldBounds, SwarmConfig, PromptProcessor, Priority, EnhancedProcessor, ModelDecomposer, DecompositionStrategy, LayerType, Tensor, TensorOps, WingbeatRng, InferenceEngine, WingbeatError, SwarmMetricsSnapshot};
+use wingbeat::core::subgraph::{ComputeNode, Operation, NodeState};
+use wingbeat::computation::model_decomposer::ModelLayer;
+use wingbeat::computation::prompt_processor::PromptStatus;
+use std::collections::HashMap;
+use uuid::Uuid;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
 #[tokio::test]
 async fn test_subgraph_creation() {
@@ -54,7 +60,7 @@ async fn test_prompt_processing() {
     let swarm = Arc::new(TornadoSwarm::new());
     let processor = PromptProcessor::new(swarm);
     
-    let prompt_id = processor.send_prompt("Test prompt").await;
+    let prompt_id = processor.send_prompt("Test prompt").await.unwrap();
     
     // Process a few steps
     for _ in 0..3 {
@@ -72,4 +78,1192 @@ async fn test_vec3_distance() {
     let v2 = Vec3::new(3.0, 4.0, 0.0);
     
     assert_eq!(v1.distance(&v2), 5.0);
-} 
\ No newline at end of file
+} 
+
+#[tokio::test]
+async fn test_vec3_math() {
+    let v = Vec3::new(3.0, 0.0, 4.0);
+    assert_eq!(v.length(), 5.0);
+    assert_eq!(v.normalize(), Vec3::new(0.6, 0.0, 0.8));
+    assert_eq!(Vec3::new(0.0, 0.0, 0.0).normalize(), Vec3::new(0.0, 0.0, 0.0));
+    
+    // Orthogonal vectors have a zero dot product
+    assert_eq!(Vec3::new(1.0, 2.0, 0.0).dot(Vec3::new(-2.0, 1.0, 5.0)), 0.0);
+    
+    assert_eq!(v + Vec3::new(1.0, 1.0, 1.0), Vec3::new(4.0, 1.0, 5.0));
+    assert_eq!(v - v, Vec3::new(0.0, 0.0, 0.0));
+    assert_eq!(v * 2.0, v.scale(2.0));
+}
+
+#[tokio::test]
+async fn test_process_with_model() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let mut processor = EnhancedProcessor::new(swarm.clone());
+    
+    let result = processor
+        .process_with_model("Test prompt", DecompositionStrategy::AttentionHeads)
+        .await
+        .unwrap();
+    
+    assert!(matches!(processor.decomposer.strategy, DecompositionStrategy::AttentionHeads));
+    assert!(result.final_output.contains("15 subgraphs"));
+    assert_eq!(swarm.tornadoes.read().await.len(), 3);
+}
+
+#[tokio::test]
+async fn test_warm_pool_reuses_resident_subgraphs() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let mut processor = EnhancedProcessor::new(Arc::clone(&swarm)).with_warm_pool(true);
+    
+    let first = processor.process_prompt("first prompt").await.unwrap();
+    let swept = swarm.metrics().subgraphs_swept;
+    assert_eq!(swept, first.per_subgraph.len() as u64);
+    
+    // The second prompt runs on the same resident subgraphs without decomposing again
+    let second = processor.process_prompt("a second longer prompt").await.unwrap();
+    let ids = |result: &wingbeat::computation::enhanced_processor::SwarmPromptResult| {
+        result.per_subgraph.iter().map(|r| r.id).collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&second), ids(&first));
+    assert_eq!(swarm.metrics().subgraphs_swept, swept);
+    assert_eq!(swarm.metrics().subgraphs_released, 0);
+    
+    // Changing the strategy evicts the old pool and decomposes afresh
+    let third = processor.process_with_model("third", DecompositionStrategy::AttentionHeads).await.unwrap();
+    assert_eq!(swarm.metrics().subgraphs_released, swept);
+    assert_eq!(swarm.metrics().subgraphs_swept, swept + third.per_subgraph.len() as u64);
+}
+
+#[tokio::test]
+async fn test_process_prompt_reports_each_subgraph() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let mut processor = EnhancedProcessor::new(swarm);
+    
+    let result = processor
+        .process_with_model("typed results", DecompositionStrategy::LayerWise)
+        .await
+        .unwrap();
+    
+    let types: Vec<SubgraphType> = result.per_subgraph.iter().map(|r| r.subgraph_type.clone()).collect();
+    assert_eq!(types, vec![SubgraphType::Embedding, SubgraphType::Attention, SubgraphType::FeedForward, SubgraphType::Output]);
+    assert_eq!(result.final_output, "'typed results' processed through 4 subgraphs");
+    
+    // The pretty report still carries one labelled line per subgraph
+    let report = result.to_string();
+    assert!(report.contains(&format!("Subgraph 0: {}", result.per_subgraph[0])));
+    assert!(report.contains("[Output: "));
+}
+
+#[tokio::test]
+async fn test_seeded_swarm_is_reproducible() {
+    let mut rng_a = WingbeatRng::seed_from_u64(11);
+    let mut rng_b = WingbeatRng::seed_from_u64(11);
+    
+    let sg_a = Subgraph::new_seeded(&mut rng_a);
+    let sg_b = Subgraph::new_seeded(&mut rng_b);
+    assert_eq!(sg_a.id, sg_b.id);
+    assert_eq!(sg_a.tornado_strength, sg_b.tornado_strength);
+    
+    let t_a = Tornado::new_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng_a);
+    let t_b = Tornado::new_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng_b);
+    assert_eq!(t_a.id, t_b.id);
+    assert_eq!(t_a.radius, t_b.radius);
+    assert_eq!(t_a.angular_velocity, t_b.angular_velocity);
+    assert_eq!(t_a.height, t_b.height);
+}
+
+#[tokio::test]
+async fn test_seeded_prompt_fragmentation() {
+    let prompt = "one two three four five six seven eight nine ten";
+    let mut fragmentations = Vec::new();
+    
+    for _ in 0..2 {
+        let processor = PromptProcessor::with_rng(
+            Arc::new(TornadoSwarm::new()),
+            WingbeatRng::seed_from_u64(5),
+        );
+        let prompt_id = processor.send_prompt(prompt).await.unwrap();
+        let prompts = processor.active_prompts.read().await;
+        let fragments: Vec<String> = prompts[&prompt_id].fragments.iter()
+            .map(|f| f.content.clone())
+            .collect();
+        fragmentations.push((prompt_id, fragments));
+    }
+    
+    assert_eq!(fragmentations[0], fragmentations[1]);
+}
+
+#[tokio::test]
+async fn test_reintegration_orders_attention_heads() {
+    let mut decomposer = ModelDecomposer::new();
+    decomposer.create_sample_model();
+    let subgraphs = decomposer.decompose_model(DecompositionStrategy::AttentionHeads).await;
+    assert_eq!(subgraphs.len(), 3 + 12);
+    
+    // Label each result by its position, then feed them in reverse order
+    let mut labelled = Vec::new();
+    for subgraph in &subgraphs {
+        let graph = subgraph.graph.read().await;
+        let node = graph.node_weights().next().unwrap();
+        let label = match node.metadata.get("head_index") {
+            Some(head) => format!("head{}", head),
+            None => format!("{:?}", subgraph.subgraph_type),
+        };
+        labelled.push((subgraph.id, label));
+    }
+    
+    let mut results = HashMap::new();
+    for (id, label) in labelled.into_iter().rev() {
+        results.insert(id, label);
+    }
+    
+    let combined = decomposer.reintegrate_results(results).await;
+    assert_eq!(
+        combined,
+        "Embedding head0 head1 head2 head3 head4 head5 head6 head7 head8 head9 head10 head11 FeedForward Output"
+    );
+}
+
+#[tokio::test]
+async fn test_tensor_parallel_shards_reassemble_matmul() {
+    let mut decomposer = ModelDecomposer::new();
+    decomposer.model_layers = vec![ModelLayer {
+        id: Uuid::new_v4(),
+        layer_type: LayerType::FeedForward,
+        parameters: HashMap::new(),
+        input_size: 3,
+        output_size: 5,
+        dependencies: vec![],
+    }];
+    let subgraphs = decomposer.decompose_model(DecompositionStrategy::TensorParallel { shards: 2 }).await;
+    assert_eq!(subgraphs.len(), 3); // Two shards plus the aggregation node
+    
+    let mut rng = WingbeatRng::seed_from_u64(8);
+    let input = Tensor::random_seeded(vec![2, 3], &mut rng);
+    let weight = Tensor::random_seeded(vec![3, 5], &mut rng);
+    
+    // Each shard multiplies by its own column slice of the weight; the aggregation node has none
+    let mut shard_outputs = HashMap::new();
+    for subgraph in &subgraphs[..2] {
+        shard_outputs.insert(subgraph.id, decomposer.shard_matmul(subgraph.id, &input, &weight).unwrap());
+    }
+    assert_eq!(decomposer.shard_weight(subgraphs[0].id, &weight).unwrap().shape, vec![3, 3]);
+    assert!(decomposer.shard_weight(subgraphs[2].id, &weight).is_err());
+    assert!(decomposer.shard_weight(subgraphs[0].id, &weight.transpose()).is_err());
+    
+    let combined = decomposer.reintegrate_shards(shard_outputs).unwrap();
+    assert_eq!(combined.len(), 1);
+    assert_eq!(combined[0].shape, vec![2, 5]);
+    assert_eq!(combined[0].data, input.matmul(&weight).unwrap().data);
+}
+
+#[test]
+fn test_validate_reports_dimension_mismatch() {
+    let mut decomposer = ModelDecomposer::new();
+    decomposer.create_sample_model();
+    assert!(decomposer.validate().is_ok());
+    
+    // Attention now expects 512 features but the embedding still produces 768
+    decomposer.model_layers[1].input_size = 512;
+    let errors = decomposer.validate().unwrap_err();
+    assert_eq!(
+        errors,
+        vec!["Layer 1 (Attention) expects input size 512 but layer 0 (Embedding) outputs 768".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_subgraph_json_round_trip() {
+    let mut rng = WingbeatRng::seed_from_u64(10);
+    let mut subgraph = Subgraph::with_type_seeded(SubgraphType::Attention, &mut rng);
+    subgraph.parent = Some(rng.gen_uuid());
+    subgraph.children = vec![rng.gen_uuid()];
+    
+    {
+        let mut graph = subgraph.graph.write().await;
+        let nodes: Vec<_> = (0..3)
+            .map(|i| graph.add_node(ComputeNode {
+                id: rng.gen_uuid(),
+                operation: Operation::Process(format!("step_{}", i)),
+                state: NodeState::Idle,
+                metadata: HashMap::from([("index".to_string(), i.to_string())]),
+            }))
+            .collect();
+        graph.add_edge(nodes[0], nodes[1], 0.5);
+        graph.add_edge(nodes[1], nodes[2], 0.25);
+    }
+    
+    let json = subgraph.to_json().await.unwrap();
+    let restored = Subgraph::from_json(&json).unwrap();
+    
+    assert_eq!(restored.id, subgraph.id);
+    assert_eq!(restored.parent, subgraph.parent);
+    assert_eq!(restored.children, subgraph.children);
+    assert_eq!(restored.tornado_strength, subgraph.tornado_strength);
+    assert_eq!(restored.subgraph_type, SubgraphType::Attention);
+    
+    let original = subgraph.graph.read().await;
+    let graph = restored.graph.read().await;
+    assert_eq!(graph.node_count(), 3);
+    assert_eq!(graph.edge_count(), 2);
+    
+    let ids = |g: &petgraph::graph::Graph<ComputeNode, f32>| g.node_weights().map(|n| n.id).collect::<Vec<_>>();
+    assert_eq!(ids(&graph), ids(&original));
+    
+    let edges = |g: &petgraph::graph::Graph<ComputeNode, f32>| g.raw_edges().iter()
+        .map(|e| (e.source().index(), e.target().index(), e.weight))
+        .collect::<Vec<_>>();
+    assert_eq!(edges(&graph), vec![(0, 1, 0.5), (1, 2, 0.25)]);
+    assert_eq!(edges(&graph), edges(&original));
+}
+
+#[tokio::test]
+async fn test_swarm_snapshot_restore() {
+    let mut rng = WingbeatRng::seed_from_u64(11);
+    let swarm = TornadoSwarm::new();
+    swarm.spawn_tornado_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng).await;
+    swarm.spawn_tornado_seeded(Vec3::new(5.0, 5.0, 0.0), &mut rng).await;
+    
+    {
+        let tornadoes = swarm.tornadoes.read().await;
+        for i in 0..3 {
+            let subgraph = Arc::new(RwLock::new(Subgraph::new_seeded(&mut rng)));
+            tornadoes[i % 2].sweep_up(subgraph).await;
+        }
+    }
+    
+    let snapshot = swarm.snapshot().await;
+    assert_eq!(snapshot.tornadoes.iter().map(|t| t.subgraph_ids.len()).sum::<usize>(), 3);
+    
+    // Mutate: move every subgraph into the second tornado, move its eye and release one
+    let released = {
+        let mut tornadoes = swarm.tornadoes.write().await;
+        let moved = tornadoes[0].release(usize::MAX).await;
+        for subgraph in moved {
+            tornadoes[1].sweep_up(subgraph).await;
+        }
+        tornadoes[1].eye = Vec3::new(-1.0, -1.0, -1.0);
+        tornadoes[1].release(1).await
+    };
+    assert_ne!(swarm.snapshot().await, snapshot);
+    
+    swarm.restore(&snapshot, released).await.unwrap();
+    assert_eq!(swarm.snapshot().await, snapshot);
+    
+    // Restoring while a subgraph is missing fails and leaves the swarm intact
+    let missing = swarm.tornadoes.read().await[0].release(1).await;
+    assert!(swarm.restore(&snapshot, Vec::new()).await.is_err());
+    swarm.restore(&snapshot, missing).await.unwrap();
+    assert_eq!(swarm.snapshot().await, snapshot);
+}
+
+#[tokio::test]
+async fn test_restore_keeps_tornado_configuration() {
+    let mut rng = WingbeatRng::seed_from_u64(34);
+    let swarm = TornadoSwarm::new();
+    let tornado = Tornado::new_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng)
+        .with_merge_on_connect(true)
+        .with_queue(4, std::time::Duration::from_millis(1));
+    swarm.tornadoes.write().await.push(tornado);
+    
+    let snapshot = swarm.snapshot().await;
+    assert!(snapshot.tornadoes[0].merge_on_connect);
+    swarm.restore(&snapshot, Vec::new()).await.unwrap();
+    
+    // The restored tornado still merges on connect and still drains its queue into itself
+    let tornadoes = swarm.tornadoes.read().await;
+    assert!(tornadoes[0].merge_on_connect);
+    assert!(tornadoes[0].intake.is_some());
+    tornadoes[0].sweep_up(Arc::new(RwLock::new(Subgraph::new_seeded(&mut rng)))).await;
+    tokio::time::timeout(std::time::Duration::from_secs(2), async {
+        while tornadoes[0].subgraph_count().await < 1 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("queued subgraph reaches the restored tornado");
+    
+    // A tornado rebuilt from a serialized snapshot only gets what the snapshot records
+    let fresh = TornadoSwarm::new();
+    fresh.restore(&snapshot, Vec::new()).await.unwrap();
+    let tornadoes = fresh.tornadoes.read().await;
+    assert!(tornadoes[0].merge_on_connect);
+    assert!(tornadoes[0].intake.is_none());
+}
+
+#[tokio::test]
+async fn test_swarm_metrics_count_sweeps_and_releases() {
+    let mut rng = WingbeatRng::seed_from_u64(12);
+    let swarm = TornadoSwarm::new();
+    swarm.spawn_tornado_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng).await;
+    assert_eq!(swarm.metrics(), SwarmMetricsSnapshot::default());
+    
+    let tornadoes = swarm.tornadoes.read().await;
+    for _ in 0..3 {
+        tornadoes[0].sweep_up(Arc::new(RwLock::new(Subgraph::new_seeded(&mut rng)))).await;
+    }
+    tornadoes[0].spin().await;
+    tornadoes[0].release(2).await;
+    
+    let metrics = swarm.metrics();
+    assert_eq!(metrics.subgraphs_swept, 3);
+    assert_eq!(metrics.subgraphs_released, 2);
+    assert_eq!(metrics.spins, 1);
+    assert!(metrics.connections_detected <= 3);
+}
+
+#[tokio::test]
+async fn test_send_prompt_into_full_swarm_errors() {
+    let swarm = Arc::new(TornadoSwarm::with_max_tornadoes(0));
+    assert!(swarm.spawn_tornado(Vec3::new(0.0, 0.0, 0.0)).await.is_none());
+    
+    let processor = PromptProcessor::new(swarm);
+    let err = processor.send_prompt("nowhere to go").await.unwrap_err();
+    assert!(matches!(err, WingbeatError::NoTornadoes));
+    assert!(processor.active_prompts.read().await.is_empty());    
+    // A failure dispatching an earlier queued prompt doesn't strand the new one either
+    processor.queue_prompt("ahead in line", Priority::High).await;
+    let err = processor.send_prompt("stuck behind it").await.unwrap_err();
+    assert!(matches!(err, WingbeatError::NoTornadoes));
+    assert!(processor.active_prompts.read().await.is_empty());
+    assert!(processor.dispatch_queued().await.unwrap().is_empty());
+}
+
+#[test]
+fn test_type_compatibility_rules() {
+    let typed = |t: SubgraphType| {
+        let mut subgraph = Subgraph::with_type(t);
+        subgraph.tornado_strength = 0.5;
+        subgraph
+    };
+    let embedding = typed(SubgraphType::Embedding);
+    let attention = typed(SubgraphType::Attention);
+    let feed_forward = typed(SubgraphType::FeedForward);
+    let output = typed(SubgraphType::Output);
+    
+    assert!(embedding.can_connect_with(&attention));
+    assert!(attention.can_connect_with(&embedding));
+    assert!(attention.can_connect_with(&feed_forward));
+    assert!(feed_forward.can_connect_with(&output));
+    assert!(!embedding.can_connect_with(&typed(SubgraphType::Embedding)));
+    assert!(!embedding.can_connect_with(&output));
+    assert_eq!(embedding.compatibility(&attention), 1.0);
+    
+    // Strength still matters for allowed pairs
+    let mut weak_attention = typed(SubgraphType::Attention);
+    weak_attention.tornado_strength = 0.65;
+    assert!((embedding.compatibility(&weak_attention) - 0.5).abs() < 1e-5);
+    weak_attention.tornado_strength = 0.9;
+    assert!(!embedding.can_connect_with(&weak_attention));
+    
+    // Rules are configurable
+    let rules = CompatibilityRules::new(0.3).allow(SubgraphType::Embedding, SubgraphType::Output);
+    assert_eq!(embedding.compatibility_with(&output, &rules), 1.0);
+    assert_eq!(embedding.compatibility_with(&attention, &rules), 0.0);
+}
+
+#[tokio::test]
+async fn test_merge_keeps_edges_and_provenance() {
+    let mut rng = WingbeatRng::seed_from_u64(18);
+    let two_node_subgraph = |rng: &mut WingbeatRng| {
+        let mut subgraph = Subgraph::new_seeded(rng);
+        subgraph.children.push(rng.gen_uuid());
+        let mut graph = subgraph.graph.try_write().unwrap();
+        let nodes: Vec<_> = (0..2)
+            .map(|i| graph.add_node(ComputeNode {
+                id: rng.gen_uuid(),
+                operation: Operation::Process(format!("step_{}", i)),
+                state: NodeState::Idle,
+                metadata: HashMap::new(),
+            }))
+            .collect();
+        graph.add_edge(nodes[0], nodes[1], 1.0);
+        drop(graph);
+        subgraph
+    };
+    
+    let mut first = two_node_subgraph(&mut rng);
+    let second = two_node_subgraph(&mut rng);
+    let second_id = second.id;
+    let first_nodes: Vec<Uuid> = first.graph.read().await.node_weights().map(|n| n.id).collect();
+    let second_nodes: Vec<Uuid> = second.graph.read().await.node_weights().map(|n| n.id).collect();
+    
+    first.merge(second).await.unwrap();
+    
+    let graph = first.graph.read().await;
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.edge_count(), 3);
+    
+    // The carried-over edge connects the merged copies of the second subgraph's nodes
+    let edge = &graph.raw_edges()[1];
+    assert_eq!(graph[edge.source()].id, second_nodes[0]);
+    assert_eq!(graph[edge.target()].id, second_nodes[1]);
+    
+    // and the first subgraph's sink now feeds the second one's source
+    let link = &graph.raw_edges()[2];
+    assert_eq!(graph[link.source()].id, first_nodes[1]);
+    assert_eq!(graph[link.target()].id, second_nodes[0]);
+    
+    assert_eq!(first.children.len(), 2);
+    assert_eq!(first.provenance, vec![second_id]);
+    
+    let clone = first.clone();
+    drop(graph);
+    assert!(first.merge(clone).await.is_err());
+}
+
+#[tokio::test]
+async fn test_run_node_applies_each_operation() {
+    let subgraph = Subgraph::new();
+    let operations = [
+        (Operation::Transform("upper".to_string()), "swarm of wings", "SWARM OF WINGS"),
+        (Operation::Split, "swarm of wings", "swarm\nof\nwings"),
+        (Operation::Merge, "swarm\nof\nwings", "swarm of wings"),
+        (Operation::Aggregate, "swarm\n\n of \nwings", "swarm of wings"),
+        (Operation::Process("layer".to_string()), "swarm of wings", "swarm of wings"),
+        (Operation::Filter("of".to_string()), "swarm of wings of air", "swarm wings air"),
+    ];
+    
+    let mut ids = Vec::new();
+    {
+        let mut graph = subgraph.graph.write().await;
+        for (operation, _, _) in &operations {
+            let id = Uuid::new_v4();
+            graph.add_node(ComputeNode {
+                id,
+                operation: operation.clone(),
+                state: NodeState::Idle,
+                metadata: HashMap::new(),
+            });
+            ids.push(id);
+        }
+    }
+    
+    for (id, (operation, input, expected)) in ids.iter().zip(&operations) {
+        let output = subgraph.run_node(*id, input).await.unwrap();
+        assert_eq!(&output, expected, "{:?}", operation);
+    }
+    
+    let graph = subgraph.graph.read().await;
+    assert!(graph.node_weights().all(|n| matches!(n.state, NodeState::Complete)));
+    drop(graph);
+    
+    assert!(subgraph.run_node(Uuid::new_v4(), "missing").await.is_err());
+}
+
+#[tokio::test]
+async fn test_execute_runs_chain_in_topological_order() {
+    let subgraph = Subgraph::new();
+    {
+        let mut graph = subgraph.graph.write().await;
+        let node = |operation| ComputeNode {
+            id: Uuid::new_v4(),
+            operation,
+            state: NodeState::Idle,
+            metadata: HashMap::new(),
+        };
+        // Added out of order so execution must follow the edges, not insertion
+        let upper = graph.add_node(node(Operation::Transform("upper".to_string())));
+        let split = graph.add_node(node(Operation::Split));
+        let filter = graph.add_node(node(Operation::Filter("the".to_string())));
+        graph.add_edge(filter, upper, 1.0);
+        graph.add_edge(upper, split, 1.0);
+    }
+    
+    let output = subgraph.execute("ride the wind").await.unwrap();
+    assert_eq!(output, "RIDE\nWIND");
+    
+    // Two sources feeding one sink are joined
+    let fan_in = Subgraph::new();
+    {
+        let mut graph = fan_in.graph.write().await;
+        let node = |operation| ComputeNode {
+            id: Uuid::new_v4(),
+            operation,
+            state: NodeState::Idle,
+            metadata: HashMap::new(),
+        };
+        let a = graph.add_node(node(Operation::Filter("b".to_string())));
+        let b = graph.add_node(node(Operation::Transform("upper".to_string())));
+        let sink = graph.add_node(node(Operation::Merge));
+        graph.add_edge(a, sink, 1.0);
+        graph.add_edge(b, sink, 1.0);
+    }
+    assert_eq!(fan_in.execute("a b").await.unwrap(), "a A B");
+}
+
+#[tokio::test]
+async fn test_infer_distributed_matches_local() {
+    let mut engine = InferenceEngine::new("simple");
+    engine.init_transformer_seeded(2, 8, 30, &mut WingbeatRng::seed_from_u64(25));
+    let local = engine.infer("wings over water").unwrap();
+    
+    let swarm = Arc::new(TornadoSwarm::new());
+    let mut processor = EnhancedProcessor::new(Arc::clone(&swarm));
+    let distributed = processor.infer_distributed(&engine, "wings over water").await.unwrap();
+    assert_eq!(distributed, local);
+    
+    // Every layer passed through the swarm and nothing was left behind
+    let metrics = swarm.metrics();
+    assert_eq!(metrics.subgraphs_swept, engine.layer_operations.len() as u64);
+    assert_eq!(metrics.subgraphs_released, engine.layer_operations.len() as u64);
+}
+
+#[tokio::test]
+async fn test_tornado_queue_applies_backpressure() {
+    let mut rng = WingbeatRng::seed_from_u64(26);
+    let tornado = Arc::new(
+        Tornado::new_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng)
+            .with_queue(2, std::time::Duration::from_millis(50)),
+    );
+    
+    // The drain task takes the first subgraph at once; the next two fill the queue
+    for _ in 0..3 {
+        tornado.sweep_up(Arc::new(RwLock::new(Subgraph::new_seeded(&mut rng)))).await;
+    }
+    assert_eq!(tornado.queue_depth(), 2);
+    
+    // A fourth sweep waits for the drain instead of being dropped
+    let started = std::time::Instant::now();
+    tornado.sweep_up(Arc::new(RwLock::new(Subgraph::new_seeded(&mut rng)))).await;
+    assert!(started.elapsed() >= std::time::Duration::from_millis(25));
+    
+    tokio::time::timeout(std::time::Duration::from_secs(2), async {
+        while tornado.subgraphs.read().await.len() < 4 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("queued subgraphs are drained");
+    assert_eq!(tornado.queue_depth(), 0);
+    assert_eq!(tornado.metrics.snapshot().subgraphs_swept, 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_spin_and_merge_do_not_deadlock() {
+    let mut rng = WingbeatRng::seed_from_u64(27);
+    let tornado = Arc::new(Tornado::new_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng));
+    let mut ids = Vec::new();
+    for _ in 0..4 {
+        let mut subgraph = Subgraph::with_type_seeded(SubgraphType::Attention, &mut rng);
+        subgraph.tornado_strength = 0.5;
+        ids.push(subgraph.id);
+        tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+    }
+    
+    let mut handles = Vec::new();
+    for _ in 0..4 {
+        let tornado = Arc::clone(&tornado);
+        handles.push(tokio::spawn(async move {
+            for _ in 0..50 {
+                tornado.spin().await;
+            }
+        }));
+    }
+    // Every ordered pair, so each pair is merged concurrently in both directions
+    for (i, &a) in ids.iter().enumerate() {
+        for &b in ids.iter().skip(i + 1) {
+            for (target, source) in [(a, b), (b, a)] {
+                let tornado = Arc::clone(&tornado);
+                handles.push(tokio::spawn(async move {
+                    tornado.merge_subgraphs(target, source).await.unwrap();
+                }));
+            }
+        }
+    }
+    
+    tokio::time::timeout(std::time::Duration::from_secs(10), futures::future::join_all(handles))
+        .await
+        .expect("spins and merges finish without deadlocking");
+    assert!(tornado.merge_subgraphs(ids[0], ids[0]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_attention_heads_follow_layer_head_count() {
+    let mut decomposer = ModelDecomposer::new();
+    decomposer.num_heads = 4;
+    decomposer.model_layers = vec![
+        ModelLayer {
+            id: Uuid::new_v4(),
+            layer_type: LayerType::Attention,
+            parameters: HashMap::from([("num_heads".to_string(), 12.0)]),
+            input_size: 768,
+            output_size: 768,
+            dependencies: vec![],
+        },
+        ModelLayer {
+            id: Uuid::new_v4(),
+            layer_type: LayerType::Attention,
+            parameters: HashMap::new(),
+            input_size: 768,
+            output_size: 768,
+            dependencies: vec![],
+        },
+    ];
+    
+    let subgraphs = decomposer.decompose_model(DecompositionStrategy::AttentionHeads).await;
+    assert_eq!(subgraphs.len(), 12 + 4);
+    for subgraph in &subgraphs[..12] {
+        assert_eq!(decomposer.subgraph_metadata[&subgraph.id]["head_count"], "12");
+    }
+    // The second layer falls back to the decomposer's head count
+    assert_eq!(decomposer.subgraph_metadata[&subgraphs[12].id]["head_count"], "4");
+}
+
+#[tokio::test]
+async fn test_token_wise_chunks_cover_prompt() {
+    let mut decomposer = ModelDecomposer::new();
+    decomposer.create_sample_model();
+    decomposer.token_count = 10;
+    let subgraphs = decomposer.decompose_model(DecompositionStrategy::TokenWise { chunks: 4 }).await;
+    assert_eq!(subgraphs.len(), 4 * 4);
+    
+    // Chunks of the first layer tile the ten tokens without gaps
+    let mut boundaries = vec![0];
+    for subgraph in &subgraphs[..4] {
+        let metadata = &decomposer.subgraph_metadata[&subgraph.id];
+        assert_eq!(metadata["token_start"].parse::<usize>().unwrap(), *boundaries.last().unwrap());
+        boundaries.push(metadata["token_end"].parse().unwrap());
+    }
+    assert_eq!(boundaries, vec![0, 3, 6, 8, 10]);
+}
+
+#[tokio::test]
+async fn test_overlapping_tornadoes_merge() {
+    let swarm = TornadoSwarm::new().with_merge_on_overlap(true);
+    let mut rng = WingbeatRng::seed_from_u64(31);
+    swarm.spawn_tornado_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng).await;
+    swarm.spawn_tornado_seeded(Vec3::new(1.0, 0.0, 0.0), &mut rng).await;
+    swarm.spawn_tornado_seeded(Vec3::new(500.0, 0.0, 0.0), &mut rng).await;
+    
+    let mut ids = Vec::new();
+    {
+        let tornadoes = swarm.tornadoes.read().await;
+        for tornado in tornadoes.iter().take(2) {
+            for _ in 0..2 {
+                let subgraph = Subgraph::new_seeded(&mut rng);
+                ids.push(subgraph.id);
+                tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+            }
+        }
+    }
+    let survivor = swarm.tornadoes.read().await[0].id;
+    
+    // A zero time step doesn't move anything, so only the overlap decides
+    swarm.simulate_step(0.0).await;
+    
+    let tornadoes = swarm.tornadoes.read().await;
+    assert_eq!(tornadoes.len(), 2);
+    assert_eq!(tornadoes[0].id, survivor);
+    let held = tornadoes[0].subgraphs.read().await;
+    assert_eq!(held.len(), 4);
+    assert!(ids.iter().all(|id| held.contains_key(id)));
+    drop(held);
+    
+    // Merging by hand works the same way and rejects unknown tornadoes
+    let distant = tornadoes[1].id;
+    drop(tornadoes);
+    assert!(swarm.merge_tornadoes(survivor, Uuid::new_v4()).await.is_err());
+    assert!(swarm.merge_tornadoes(survivor, survivor).await.is_err());
+    swarm.merge_tornadoes(distant, survivor).await.unwrap();
+    let tornadoes = swarm.tornadoes.read().await;
+    assert_eq!(tornadoes.len(), 1);
+    assert_eq!(tornadoes[0].id, distant);
+    assert_eq!(tornadoes[0].subgraphs.read().await.len(), 4);
+}
+
+#[tokio::test]
+async fn test_merging_queued_tornado_keeps_queued_subgraphs() {
+    let mut rng = WingbeatRng::seed_from_u64(32);
+    let swarm = TornadoSwarm::new();
+    let target = swarm.spawn_tornado_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng).await.unwrap();
+    let source = Tornado::new_seeded(Vec3::new(500.0, 0.0, 0.0), &mut rng)
+        .with_queue(4, std::time::Duration::from_millis(50));
+    let source_id = source.id;
+    swarm.tornadoes.write().await.push(source.clone());
+    
+    // The drain task takes the first subgraph at once; the rest are still queued at merge time
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let subgraph = Subgraph::new_seeded(&mut rng);
+        ids.push(subgraph.id);
+        source.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+    }
+    assert!(source.queue_depth() > 0);
+    swarm.merge_tornadoes(target, source_id).await.unwrap();
+    
+    // Sweeping into the absorbed tornado afterwards still reaches the target
+    let late = Subgraph::new_seeded(&mut rng);
+    ids.push(late.id);
+    source.sweep_up(Arc::new(RwLock::new(late))).await;
+    
+    let tornadoes = swarm.tornadoes.read().await;
+    tokio::time::timeout(std::time::Duration::from_secs(2), async {
+        while tornadoes[0].subgraphs.read().await.len() < ids.len() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("queued subgraphs reach the target");
+    let held = tornadoes[0].subgraphs.read().await;
+    assert!(ids.iter().all(|id| held.contains_key(id)));
+    assert!(source.subgraphs.read().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_world_bounds_reflect_tornadoes() {
+    let bounds = WorldBounds::new(Vec3::new(-10.0, -10.0, -10.0), Vec3::new(10.0, 10.0, 10.0));
+    let swarm = TornadoSwarm::new().with_bounds(bounds);
+    swarm.spawn_tornado(Vec3::new(0.0, 0.0, 0.0)).await;
+    {
+        let mut tornadoes = swarm.tornadoes.write().await;
+        tornadoes[0].eye = Vec3::new(9.5, 0.0, 0.0);
+        tornadoes[0].velocity = Vec3::new(10.0, 0.0, 0.0);
+    }
+    
+    // One step carries the tornado about a unit past the wall at x = 10
+    swarm.simulate_step(0.1).await;
+    
+    let tornadoes = swarm.tornadoes.read().await;
+    assert!(bounds.contains(tornadoes[0].eye));
+    assert!(tornadoes[0].eye.x < 10.0);
+    assert_eq!(tornadoes[0].velocity, Vec3::new(-10.0, 0.0, 0.0));
+}
+
+#[tokio::test]
+async fn test_seeded_simulate_step_is_reproducible() {
+    let run = || async {
+        let mut rng = WingbeatRng::seed_from_u64(33);
+        let swarm = TornadoSwarm::new();
+        swarm.spawn_tornado_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng).await;
+        swarm.spawn_tornado_seeded(Vec3::new(500.0, 0.0, 0.0), &mut rng).await;
+        for _ in 0..5 {
+            swarm.simulate_step_seeded(1.0, &mut rng).await;
+        }
+        let eyes: Vec<Vec3> = swarm.tornadoes.read().await.iter().map(|t| t.eye).collect();
+        eyes
+    };
+    
+    let eyes = run().await;
+    assert_eq!(eyes, run().await);
+    assert_ne!(eyes[0], Vec3::new(0.0, 0.0, 0.0));
+}
+
+#[tokio::test]
+async fn test_tornado_clone_shares_and_deep_clone_copies() {
+    let mut rng = WingbeatRng::seed_from_u64(28);
+    let tornado = Tornado::new_seeded(Vec3::new(1.0, 2.0, 3.0), &mut rng);
+    let subgraph = Subgraph::new_seeded(&mut rng);
+    subgraph.graph.write().await.add_node(ComputeNode {
+        id: Uuid::new_v4(),
+        operation: Operation::Transform("upper".to_string()),
+        state: NodeState::Idle,
+        metadata: HashMap::new(),
+    });
+    let subgraph_id = subgraph.id;
+    tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+    
+    let shallow = tornado.clone();
+    let deep = tornado.deep_clone().await;
+    assert_eq!(shallow.eye, tornado.eye);
+    assert_eq!(deep.radius, tornado.radius);
+    
+    // The shallow clone sees new subgraphs; the deep clone does not
+    tornado.sweep_up(Arc::new(RwLock::new(Subgraph::new_seeded(&mut rng)))).await;
+    assert_eq!(shallow.subgraphs.read().await.len(), 2);
+    assert_eq!(deep.subgraphs.read().await.len(), 1);
+    
+    // Editing the deep copy's compute graph leaves the original alone
+    let copied = Arc::clone(&deep.subgraphs.read().await[&subgraph_id]);
+    copied.read().await.graph.write().await.clear();
+    let original = Arc::clone(&tornado.subgraphs.read().await[&subgraph_id]);
+    assert_eq!(original.read().await.graph.read().await.node_count(), 1);
+}
+
+#[tokio::test]
+async fn test_release_by_id() {
+    let mut rng = WingbeatRng::seed_from_u64(29);
+    let tornado = Tornado::new_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng);
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let subgraph = Subgraph::new_seeded(&mut rng);
+        ids.push(subgraph.id);
+        tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+    }
+    
+    let released = tornado.release_by_id(ids[1]).await.unwrap();
+    assert_eq!(released.read().await.id, ids[1]);
+    assert!(tornado.contains(ids[0]).await);
+    assert!(!tornado.contains(ids[1]).await);
+    assert!(tornado.contains(ids[2]).await);
+    
+    assert!(tornado.release_by_id(ids[1]).await.is_none());
+    assert_eq!(tornado.metrics.snapshot().subgraphs_released, 1);
+}
+
+#[tokio::test]
+async fn test_for_each_subgraph_visits_held_subgraphs() {
+    let mut rng = WingbeatRng::seed_from_u64(40);
+    let tornado = Tornado::new_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng);
+    let mut ids = Vec::new();
+    for _ in 0..4 {
+        let subgraph = Subgraph::new_seeded(&mut rng);
+        ids.push(subgraph.id);
+        tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+    }
+    tornado.release_by_id(ids[2]).await;
+    ids.remove(2);
+    ids.sort();
+    
+    let mut visited = Vec::new();
+    tornado.for_each_subgraph(|subgraph| visited.push(subgraph.id)).await;
+    visited.sort();
+    
+    assert_eq!(visited, ids);
+    assert_eq!(tornado.subgraph_ids().await, ids);
+    assert_eq!(tornado.subgraph_count().await, 3);
+}
+
+#[tokio::test]
+async fn test_total_subgraphs() {
+    let swarm = TornadoSwarm::with_max_tornadoes(20);
+    let mut rng = WingbeatRng::seed_from_u64(41);
+    for i in 0..12 {
+        swarm.spawn_tornado_seeded(Vec3::new(i as f32 * 10.0, 0.0, 0.0), &mut rng).await;
+    }
+    assert_eq!(swarm.total_subgraphs().await, 0);
+    
+    // More tornadoes than the concurrency bound, each holding a different number
+    {
+        let tornadoes = swarm.tornadoes.read().await;
+        for (i, tornado) in tornadoes.iter().enumerate() {
+            for _ in 0..i % 4 {
+                tornado.sweep_up(Arc::new(RwLock::new(Subgraph::new_seeded(&mut rng)))).await;
+            }
+        }
+    }
+    
+    assert_eq!(swarm.total_subgraphs().await, 18);
+}
+
+#[tokio::test]
+async fn test_swarm_config_ranges_are_respected() {
+    let config = SwarmConfig::default()
+        .with_radius_range(2.0..=3.0)
+        .with_height_range(100.0..=101.0);
+    let swarm = TornadoSwarm::with_config(config);
+    let mut rng = WingbeatRng::seed_from_u64(30);
+    for i in 0..20 {
+        swarm.spawn_tornado_seeded(Vec3::new(i as f32, 0.0, 0.0), &mut rng).await;
+    }
+    
+    for tornado in swarm.tornadoes.read().await.iter() {
+        assert!((2.0..=3.0).contains(&tornado.radius));
+        assert!((100.0..=101.0).contains(&tornado.height));
+        assert!((0.5..=2.0).contains(&tornado.angular_velocity));
+    }
+    
+    // A degenerate range pins the value instead of panicking
+    let fixed = TornadoSwarm::with_config(SwarmConfig::default().with_radius_range(5.0..=5.0));
+    fixed.spawn_tornado_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng).await;
+    assert_eq!(fixed.tornadoes.read().await[0].radius, 5.0);
+}
+
+#[tokio::test]
+async fn test_empty_prompts_complete_trivially() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let processor = PromptProcessor::new(Arc::clone(&swarm));
+    
+    for prompt in ["", "   "] {
+        let prompt_id = processor.send_prompt(prompt).await.unwrap();
+        assert_eq!(processor.collect_results(prompt_id).await.as_deref(), Some(""));
+    }
+    // Nothing was sent into the swarm
+    assert!(swarm.tornadoes.read().await.is_empty());
+    assert_eq!(swarm.metrics().subgraphs_swept, 0);
+}
+
+#[tokio::test]
+async fn test_high_priority_prompt_is_dispatched_first() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let processor = PromptProcessor::with_rng(swarm, WingbeatRng::seed_from_u64(36));
+    
+    let batch = processor.queue_prompt("nightly batch summary", Priority::Low).await;
+    let routine = processor.queue_prompt("routine request", Priority::Normal).await;
+    let interactive = processor.queue_prompt("interactive question", Priority::High).await;
+    
+    let order = processor.dispatch_queued().await.unwrap();
+    assert_eq!(order, vec![interactive, routine, batch]);
+    assert!(processor.dispatch_queued().await.unwrap().is_empty());
+    
+    // Immediate sends go through the same queue
+    let prompt_id = processor.send_prompt("right now").await.unwrap();
+    assert_eq!(processor.active_prompts.read().await[&prompt_id].priority, Priority::Normal);
+}
+
+#[tokio::test]
+async fn test_cancel_releases_prompt_subgraphs() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let processor = PromptProcessor::with_rng(Arc::clone(&swarm), WingbeatRng::seed_from_u64(37));
+    
+    let kept = processor.send_prompt("keep these words around").await.unwrap();
+    let cancelled = processor.send_prompt("drop every one of these words").await.unwrap();
+    let fragment_ids: Vec<Uuid> = processor.active_prompts.read().await[&cancelled]
+        .fragments.iter().map(|f| f.subgraph_id).collect();
+    let mut held = 0;
+    for tornado in swarm.tornadoes.read().await.iter() {
+        for id in &fragment_ids {
+            held += usize::from(tornado.contains(*id).await);
+        }
+    }
+    assert_eq!(held, fragment_ids.len());
+    
+    assert!(processor.cancel(cancelled).await);
+    assert!(matches!(processor.active_prompts.read().await[&cancelled].status, PromptStatus::Cancelled));
+    assert!(processor.collect_results(cancelled).await.is_none());
+    assert!(processor.process_until(cancelled, std::time::Duration::from_secs(2)).await.is_none());
+    assert!(matches!(processor.active_prompts.read().await[&cancelled].status, PromptStatus::Cancelled));
+    for tornado in swarm.tornadoes.read().await.iter() {
+        for id in &fragment_ids {
+            assert!(!tornado.contains(*id).await);
+        }
+    }
+    
+    // Other prompts are untouched, and cancelling twice is a no-op
+    assert!(processor.collect_results(kept).await.is_some());
+    assert!(!processor.cancel(cancelled).await);
+    
+    // A prompt cancelled while still queued is never sent into the swarm
+    let queued = processor.queue_prompt("never dispatched", Priority::Normal).await;
+    assert!(processor.cancel(queued).await);
+    assert!(processor.dispatch_queued().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_process_until_returns_assembled_result() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let processor = PromptProcessor::with_rng(swarm, WingbeatRng::seed_from_u64(38));
+    
+    let id = processor.send_prompt("step until done").await.unwrap();
+    let timeout = std::time::Duration::from_secs(2);
+    let started = std::time::Instant::now();
+    let result = processor.process_until(id, timeout).await;
+    
+    assert!(started.elapsed() < timeout);
+    assert_eq!(result.as_deref(), Some("STEP UNTIL DONE"));
+    assert!(processor.active_prompts.read().await[&id].fragments.iter().all(|f| f.processed));
+    
+    // Unknown prompts never complete
+    assert!(processor.process_until(Uuid::new_v4(), timeout).await.is_none());
+}
+
+#[tokio::test]
+async fn test_strength_histogram() {
+    let swarm = TornadoSwarm::new();
+    swarm.spawn_tornado(Vec3::new(0.0, 0.0, 0.0)).await;
+    swarm.spawn_tornado(Vec3::new(30.0, 0.0, 0.0)).await;
+    
+    {
+        let tornadoes = swarm.tornadoes.read().await;
+        for (i, strength) in [0.05, 0.1, 0.3, 0.55, 0.99, 1.0].into_iter().enumerate() {
+            let mut subgraph = Subgraph::new();
+            subgraph.tornado_strength = strength;
+            tornadoes[i % 2].sweep_up(Arc::new(RwLock::new(subgraph))).await;
+        }
+    }
+    
+    assert_eq!(swarm.strength_histogram(4).await, vec![2, 1, 1, 2]);
+    assert_eq!(swarm.strength_histogram(1).await, vec![6]);
+    assert!(swarm.strength_histogram(0).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_deterministic_decomposition_is_byte_identical() {
+    let id = Uuid::new_v4();
+    assert_eq!(Subgraph::with_id(id).id, id);
+    assert_eq!(Subgraph::with_id(id).tornado_strength, Subgraph::with_id(id).tornado_strength);
+    
+    let mut decomposer = ModelDecomposer::new();
+    decomposer.create_sample_model();
+    decomposer.deterministic_ids = true;
+    
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let mut serialized = Vec::new();
+        for subgraph in decomposer.decompose_model(DecompositionStrategy::TensorParallel { shards: 2 }).await {
+            serialized.push(subgraph.to_json().await.unwrap());
+        }
+        runs.push(serialized);
+    }
+    
+    assert_eq!(runs[0].len(), 12);
+    assert_eq!(runs[0], runs[1]);
+    
+    // Random ids are still the default
+    decomposer.deterministic_ids = false;
+    let random = decomposer.decompose_model(DecompositionStrategy::LayerWise).await;
+    assert_ne!(random[0].to_json().await.unwrap(), runs[0][0]);
+}
+
+#[tokio::test]
+async fn test_spin_returns_connectable_pairs() {
+    let tornado = Tornado::new(Vec3::new(0.0, 0.0, 0.0));
+    let typed = |subgraph_type, strength| {
+        let mut subgraph = Subgraph::with_type(subgraph_type);
+        subgraph.tornado_strength = strength;
+        subgraph
+    };
+    
+    // Embedding and attention may connect at close strengths; embedding and output never do
+    let embedding = typed(SubgraphType::Embedding, 0.5);
+    let attention = typed(SubgraphType::Attention, 0.6);
+    let output = typed(SubgraphType::Output, 0.5);
+    let expected = if embedding.id < attention.id {
+        (embedding.id, attention.id)
+    } else {
+        (attention.id, embedding.id)
+    };
+    
+    for subgraph in [embedding, output] {
+        tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+    }
+    assert!(tornado.spin().await.is_empty());
+    
+    tornado.sweep_up(Arc::new(RwLock::new(attention))).await;
+    assert_eq!(tornado.spin().await, vec![expected]);
+}
+
+#[tokio::test]
+async fn test_merge_on_connect_combines_pairs() {
+    let tornado = Tornado::new(Vec3::new(0.0, 0.0, 0.0)).with_merge_on_connect(true);
+    let mut ids = Vec::new();
+    for strength in [0.5, 0.55] {
+        let mut subgraph = Subgraph::with_type(SubgraphType::Attention);
+        subgraph.tornado_strength = strength;
+        subgraph.graph.write().await.add_node(ComputeNode {
+            id: Uuid::new_v4(),
+            operation: Operation::Process(format!("{}", strength)),
+            state: NodeState::Idle,
+            metadata: HashMap::new(),
+        });
+        ids.push(subgraph.id);
+        tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+    }
+    ids.sort();
+    
+    assert_eq!(tornado.spin().await, vec![(ids[0], ids[1])]);
+    
+    let held = tornado.subgraphs.read().await;
+    assert_eq!(held.len(), 1);
+    let merged = held[&ids[0]].read().await;
+    assert_eq!(merged.graph.read().await.node_count(), 2);
+    assert!(merged.provenance.contains(&ids[1]));
+}
+
+#[tokio::test]
+async fn test_merged_fragments_are_assembled_once() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let mut rng = WingbeatRng::seed_from_u64(41);
+    swarm.tornadoes.write().await.push(Tornado::new_seeded(Vec3::new(0.0, 0.0, 0.0), &mut rng).with_merge_on_connect(true));
+    let processor = PromptProcessor::with_rng(Arc::clone(&swarm), rng);
+    
+    let text = "every wing in the swarm lifts the whole storm a little higher";
+    let id = processor.send_prompt(text).await.unwrap();
+    let fragments = processor.active_prompts.read().await[&id].fragments.len();
+    let result = processor.process_until(id, std::time::Duration::from_secs(2)).await;
+    
+    // Some fragments were merged, yet each one's text appears exactly once
+    assert!(swarm.total_subgraphs().await < fragments);
+    assert_eq!(result.as_deref(), Some(text.to_uppercase().as_str()));
+}
+
+#[tokio::test]
+async fn test_merge_on_connect_merges_each_subgraph_once() {
+    let tornado = Tornado::new(Vec3::new(0.0, 0.0, 0.0)).with_merge_on_connect(true);
+    for _ in 0..3 {
+        let mut subgraph = Subgraph::with_type(SubgraphType::Attention);
+        subgraph.tornado_strength = 0.5;
+        tornado.sweep_up(Arc::new(RwLock::new(subgraph))).await;
+    }
+    
+    // All three pairs connect, but only one merge can happen without reusing a subgraph
+    assert_eq!(tornado.spin().await.len(), 3);
+    assert_eq!(tornado.subgraphs.read().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_connection_threshold() {
+    let mut sg1 = Subgraph::new();
+    let mut sg2 = Subgraph::new();
+    sg1.tornado_strength = 0.5;
+    sg2.tornado_strength = 0.75;
+    
+    assert!(sg1.can_connect_with_threshold(&sg2, 0.3));
+    assert!(!sg1.can_connect_with_threshold(&sg2, 0.2));
+    assert_eq!(sg1.can_connect_with(&sg2), sg1.can_connect_with_threshold(&sg2, 0.3));
+    
+    // The type rules still apply whatever the threshold
+    let embedding = Subgraph::with_type(SubgraphType::Embedding);
+    let output = Subgraph::with_type(SubgraphType::Output);
+    assert!(!embedding.can_connect_with_threshold(&output, 2.0));
+}
+
+#[tokio::test]
+async fn test_collect_results_runs_concurrently() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let processor = Arc::new(PromptProcessor::with_rng(Arc::clone(&swarm), WingbeatRng::seed_from_u64(39)));
+    let slow = processor.send_prompt("held up in the swarm").await.unwrap();
+    let fast = processor.send_prompt("free to go").await.unwrap();
+    
+    // Lock one of the slow prompt's subgraphs so its assembly has to wait
+    let blocked_id = processor.active_prompts.read().await[&slow].fragments[0].subgraph_id;
+    let mut blocked = None;
+    for tornado in swarm.tornadoes.read().await.iter() {
+        if let Some(subgraph) = tornado.subgraphs.read().await.get(&blocked_id) {
+            blocked = Some(Arc::clone(subgraph));
+        }
+    }
+    let blocked = blocked.unwrap();
+    let guard = blocked.write().await;
+    
+    let slow_task = tokio::spawn({
+        let processor = Arc::clone(&processor);
+        async move { processor.collect_results(slow).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    
+    // The other prompt is collected while the first is still assembling
+    let result = tokio::time::timeout(std::time::Duration::from_secs(1), processor.collect_results(fast)).await;
+    assert_eq!(result.unwrap().as_deref(), Some("FREE TO GO"));
+    assert!(!slow_task.is_finished());
+    assert!(matches!(processor.active_prompts.read().await[&slow].status, PromptStatus::Assembling));
+    
+    drop(guard);
+    assert_eq!(slow_task.await.unwrap().as_deref(), Some("HELD UP IN THE SWARM"));
+}
+
+#[tokio::test]
+async fn test_long_prompt_fragments_all_land() {
+    let swarm = Arc::new(TornadoSwarm::new());
+    let processor = PromptProcessor::with_rng(Arc::clone(&swarm), WingbeatRng::seed_from_u64(39));
+    
+    let words: Vec<String> = (0..39).map(|i| format!("w{}", i)).collect();
+    let id = processor.send_prompt(&words.join(" ")).await.unwrap();
+    let prompt = processor.active_prompts.read().await[&id].clone();
+    assert_eq!(prompt.fragments.len(), 20);
+    assert!(matches!(prompt.status, PromptStatus::InWhirlwind));
+    
+    // Every fragment's subgraph is held by some tornado and remembers its place in the prompt
+    let mut landed = HashMap::new();
+    for tornado in swarm.tornadoes.read().await.iter() {
+        for (id, subgraph) in tornado.subgraphs.read().await.iter() {
+            let graph = subgraph.read().await.graph.read().await.clone();
+            let node = graph.node_weights().next().unwrap();
+            landed.insert(*id, node.metadata["fragment_index"].clone());
+        }
+    }
+    assert_eq!(landed.len(), 20);
+    for (i, fragment) in prompt.fragments.iter().enumerate() {
+        assert_eq!(landed[&fragment.subgraph_id], i.to_string());
+    }
+}
@@ -0,0 +1,59 @@
+use proptest::prelude::*;
+use wingbeat::{Tensor, TensorOps};
+
+const EPSILON: f32 = 1e-3;
+
+/// Random 2D tensor with each dimension in 1..=8 and values in [-10, 10]
+fn tensor_2d() -> impl Strategy<Value = Tensor> {
+    (1usize..=8, 1usize..=8).prop_flat_map(|(rows, cols)| tensor_with_shape(rows, cols))
+}
+
+fn tensor_with_shape(rows: usize, cols: usize) -> impl Strategy<Value = Tensor> {
+    prop::collection::vec(-10.0f32..10.0, rows * cols)
+        .prop_map(move |data| Tensor::new(vec![rows, cols], data))
+}
+
+fn assert_close(a: &Tensor, b: &Tensor) -> Result<(), TestCaseError> {
+    prop_assert_eq!(&a.shape, &b.shape);
+    for (x, y) in a.data.iter().zip(b.data.iter()) {
+        // Relative tolerance so large products are not held to an absolute epsilon
+        prop_assert!((x - y).abs() <= EPSILON * x.abs().max(y.abs()).max(1.0), "{} != {}", x, y);
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn transpose_is_an_involution(x in tensor_2d()) {
+        let round_trip = x.transpose().transpose();
+        prop_assert_eq!(round_trip.shape, x.shape);
+        prop_assert_eq!(round_trip.data, x.data);
+    }
+
+    #[test]
+    fn add_is_commutative((a, b) in (1usize..=8, 1usize..=8)
+        .prop_flat_map(|(rows, cols)| (tensor_with_shape(rows, cols), tensor_with_shape(rows, cols))))
+    {
+        assert_close(&a.add(&b).unwrap(), &b.add(&a).unwrap())?;
+    }
+
+    #[test]
+    fn matmul_is_associative((a, b, c) in (1usize..=6, 1usize..=6, 1usize..=6, 1usize..=6)
+        .prop_flat_map(|(m, k, n, p)| (tensor_with_shape(m, k), tensor_with_shape(k, n), tensor_with_shape(n, p))))
+    {
+        let left = a.matmul(&b).unwrap().matmul(&c).unwrap();
+        let right = a.matmul(&b.matmul(&c).unwrap()).unwrap();
+        assert_close(&left, &right)?;
+    }
+
+    #[test]
+    fn softmax_rows_sum_to_one(x in tensor_2d()) {
+        let probs = x.softmax();
+        let cols = x.shape[1];
+        for row in probs.data.chunks(cols) {
+            let sum: f32 = row.iter().sum();
+            prop_assert!((sum - 1.0).abs() < EPSILON, "row sums to {}", sum);
+            prop_assert!(row.iter().all(|&p| (0.0..=1.0).contains(&p)));
+        }
+    }
+}
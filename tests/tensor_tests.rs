@@ -0,0 +1,288 @@
+use wingbeat::{Tensor, TensorOps, WingbeatError, WingbeatRng};
+use wingbeat::inference::tensor_ops::{cross_entropy, DataType};
+use wingbeat::inference::TensorPayload;
+
+#[test]
+fn test_transposed_matmul_matches_naive() {
+    let mut rng = WingbeatRng::seed_from_u64(1);
+    
+    for &(m, k, n) in &[(1, 1, 1), (3, 5, 2), (17, 130, 90), (128, 96, 200)] {
+        let a = Tensor::random_seeded(vec![m, k], &mut rng);
+        let b = Tensor::random_seeded(vec![k, n], &mut rng);
+        
+        let naive = a.matmul_naive(&b).unwrap();
+        let transposed = a.matmul_transposed(&b).unwrap();
+        let routed = a.matmul(&b).unwrap();
+        
+        assert_eq!(naive.shape, vec![m, n]);
+        assert_eq!(naive.shape, transposed.shape);
+        assert_eq!(naive, transposed);
+        assert_eq!(naive, routed);
+    }
+}
+
+#[test]
+fn test_in_place_ops_match_functional() {
+    let a = Tensor::new(vec![2, 3], vec![-1.0, 2.0, -3.0, 4.0, 0.0, 6.0]);
+    let b = Tensor::new(vec![2, 3], vec![0.5, -2.5, 1.0, 1.0, -1.0, 0.25]);
+    
+    let mut added = a.clone();
+    added.add_(&b).unwrap();
+    assert_eq!(added, a.add(&b).unwrap());
+    
+    let mut activated = a.clone();
+    activated.relu_();
+    assert_eq!(activated, a.relu());
+    
+    let mut scaled = a.clone();
+    scaled.mul_scalar_(0.5);
+    assert_eq!(scaled.data, vec![-0.5, 1.0, -1.5, 2.0, 0.0, 3.0]);
+}
+
+#[test]
+fn test_in_place_add_shape_mismatch() {
+    let mut a = Tensor::ones(vec![2, 3]);
+    let b = Tensor::ones(vec![3, 2]);
+    
+    assert!(a.add_(&b).is_err());
+    assert_eq!(a.data, vec![1.0; 6]);
+}
+
+#[test]
+fn test_slicing_and_indexing() {
+    let t = Tensor::new(vec![4, 3], (0..12).map(|x| x as f32).collect());
+    
+    let rows = t.slice_dim(0, 1, 3).unwrap();
+    assert_eq!(rows.shape, vec![2, 3]);
+    assert_eq!(rows.data, vec![3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+    
+    let cols = t.slice_dim(1, 2, 3).unwrap();
+    assert_eq!(cols.shape, vec![4, 1]);
+    assert_eq!(cols.data, vec![2.0, 5.0, 8.0, 11.0]);
+    
+    // Boundary ranges
+    assert_eq!(t.slice_dim(0, 0, 4).unwrap().data, t.data);
+    assert_eq!(t.slice_dim(1, 3, 3).unwrap().shape, vec![4, 0]);
+    assert!(t.slice_dim(0, 3, 5).is_err());
+    assert!(t.slice_dim(1, 2, 1).is_err());
+    assert!(t.slice_dim(2, 0, 1).is_err());
+    
+    let last = t.row(3).unwrap();
+    assert_eq!(last.shape, vec![1, 3]);
+    assert_eq!(last.data, vec![9.0, 10.0, 11.0]);
+    assert!(t.row(4).is_err());
+    
+    assert_eq!(t.get(&[2, 1]), Some(7.0));
+    assert_eq!(t.get(&[4, 0]), None);
+    assert_eq!(t.get(&[0]), None);
+}
+
+#[test]
+fn test_concat_and_stack() {
+    let a = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let b = Tensor::new(vec![2, 3], vec![7.0, 8.0, 9.0, 10.0, 11.0, 12.0]);
+    
+    let rows = Tensor::concat(&[a.clone(), b.clone()], 0).unwrap();
+    assert_eq!(rows.shape, vec![4, 3]);
+    assert_eq!(rows.data, (1..=12).map(|x| x as f32).collect::<Vec<_>>());
+    
+    let cols = Tensor::concat(&[a.clone(), b.clone()], 1).unwrap();
+    assert_eq!(cols.shape, vec![2, 6]);
+    assert_eq!(cols.data, vec![1.0, 2.0, 3.0, 7.0, 8.0, 9.0, 4.0, 5.0, 6.0, 10.0, 11.0, 12.0]);
+    
+    let stacked = Tensor::stack(&[a.clone(), b.clone()], 0).unwrap();
+    assert_eq!(stacked.shape, vec![2, 2, 3]);
+    assert_eq!(stacked.data, rows.data);
+    
+    let stacked_last = Tensor::stack(&[a.clone(), b.clone()], 2).unwrap();
+    assert_eq!(stacked_last.shape, vec![2, 3, 2]);
+    assert_eq!(stacked_last.get(&[1, 2, 1]), Some(12.0));
+    
+    // Mismatched non-concat dims are rejected
+    let c = Tensor::zeros(vec![3, 3]);
+    assert!(Tensor::concat(&[a.clone(), c.clone()], 0).is_ok());
+    assert!(Tensor::concat(&[a.clone(), c.clone()], 1).is_err());
+    assert!(Tensor::stack(&[a, c], 0).is_err());
+    assert!(Tensor::concat(&[], 0).is_err());
+}
+
+#[test]
+fn test_reductions() {
+    let t = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 4.0, 5.0, 9.0]);
+    
+    let sum_rows = t.sum_dim(0);
+    assert_eq!(sum_rows.shape, vec![1, 3]);
+    assert_eq!(sum_rows.data, vec![5.0, 7.0, 12.0]);
+    
+    let mean_rows = t.mean_dim(0);
+    assert_eq!(mean_rows.data, vec![2.5, 3.5, 6.0]);
+    
+    let mean_cols = t.mean_dim(1);
+    assert_eq!(mean_cols.shape, vec![2, 1]);
+    assert_eq!(mean_cols.data, vec![2.0, 6.0]);
+    
+    let v = Tensor::new(vec![5], vec![0.1, -3.0, 7.5, 7.5, 2.0]);
+    assert_eq!(v.argmax(), 2);
+    
+    assert_eq!(t.argmax(), 5);
+    assert_eq!(t.argmax_dim(1), vec![2, 2]);
+    assert_eq!(t.argmax_dim(0), vec![1, 1, 1]);
+}
+
+#[test]
+fn test_dropout() {
+    let mut rng = WingbeatRng::seed_from_u64(21);
+    let t = Tensor::random_seeded(vec![16, 16], &mut rng);
+    
+    assert_eq!(t.dropout(0.5, false, &mut rng).data, t.data);
+    assert_eq!(t.dropout(0.0, true, &mut rng).data, t.data);
+    
+    let dropped = t.dropout(0.5, true, &mut rng);
+    let zeros = dropped.data.iter().filter(|&&x| x == 0.0).count();
+    assert!(zeros > 64 && zeros < 192, "dropped {} of 256", zeros);
+    for (d, x) in dropped.data.iter().zip(&t.data) {
+        assert!(*d == 0.0 || (d - x * 2.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_log_softmax_and_cross_entropy() {
+    // Hand-computed: log-sum-exp(1, 2, 3) = 3.407606
+    let logits = Tensor::new(vec![1, 3], vec![1.0, 2.0, 3.0]);
+    let log_probs = logits.log_softmax(1);
+    for (got, expected) in log_probs.data.iter().zip([-2.407606, -1.407606, -0.407606]) {
+        assert!((got - expected).abs() < 1e-5);
+    }
+    
+    // Stable for large logits where a naive exp would overflow
+    let large = Tensor::new(vec![1, 3], vec![1001.0, 1002.0, 1003.0]).log_softmax(1);
+    assert!((large.data[2] + 0.407606).abs() < 1e-4);
+    
+    let loss = cross_entropy(&Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 3.0, 2.0, 1.0]), &[2, 1]).unwrap();
+    assert!((loss - (0.407606 + 1.407606) / 2.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_cross_entropy_rejects_bad_targets() {
+    let logits = Tensor::new(vec![2, 3], vec![1.0, 2.0, 3.0, 3.0, 2.0, 1.0]);
+    
+    // A target past the vocabulary must not read into the next row
+    assert!(matches!(cross_entropy(&logits, &[3, 0]), Err(WingbeatError::TokenOutOfRange { id: 3, vocab_size: 3 })));
+    assert!(matches!(cross_entropy(&logits, &[0, 3]), Err(WingbeatError::TokenOutOfRange { id: 3, vocab_size: 3 })));
+    assert!(matches!(cross_entropy(&logits, &[0]), Err(WingbeatError::ShapeMismatch { .. })));
+    assert!(matches!(cross_entropy(&Tensor::new(vec![3], vec![0.0; 3]), &[0]), Err(WingbeatError::InvalidShape(_))));
+}
+
+#[test]
+fn test_tensor_payload_round_trip() {
+    let tensor = Tensor::random_seeded(vec![2, 768], &mut WingbeatRng::seed_from_u64(11));
+    let value = TensorPayload::to_value(&tensor);
+    
+    // The buffer travels as one base64 string, not a 1536-element array
+    assert!(value["data"].is_string());
+    
+    let json = serde_json::to_string(&value).unwrap();
+    let decoded = TensorPayload::from_value(serde_json::from_str(&json).unwrap()).unwrap();
+    assert_eq!(decoded.shape, tensor.shape);
+    assert_eq!(decoded.data, tensor.data);
+    
+    let mut truncated = TensorPayload::from_tensor(&tensor);
+    truncated.shape = vec![3, 768];
+    assert!(truncated.to_tensor().is_err());
+    
+    // A dtype the crate doesn't know or a corrupt buffer is a decoding error, not a silent fallback
+    let mut unknown = value.clone();
+    unknown["dtype"] = serde_json::json!("BFloat16");
+    assert!(matches!(TensorPayload::from_value(unknown), Err(WingbeatError::Serialization(_))));
+    let mut corrupt = TensorPayload::from_tensor(&tensor);
+    corrupt.data = "not base64!".to_string();
+    assert!(matches!(corrupt.to_tensor(), Err(WingbeatError::Serialization(_))));
+    
+    let ints = Tensor::new(vec![2], vec![1.0, 2.0]).to_dtype(DataType::Int64);
+    assert_eq!(TensorPayload::from_value(TensorPayload::to_value(&ints)).unwrap().dtype, DataType::Int64);
+}
+
+#[test]
+fn test_tensor_equality() {
+    let a = Tensor::new(vec![2], vec![0.1, 0.2]);
+    let b = Tensor::new(vec![2], vec![0.1 + 1e-7, 0.2]);
+    
+    assert_eq!(a, a.clone());
+    assert_ne!(a, b);
+    assert!(a.approx_eq(&b, 1e-6));
+    assert!(!a.approx_eq(&b, 1e-8));
+    
+    // Shapes must match, and NaN is never equal
+    assert!(!a.approx_eq(&Tensor::new(vec![1, 2], vec![0.1, 0.2]), 1.0));
+    let nan = Tensor::new(vec![2], vec![f32::NAN, 0.2]);
+    assert_ne!(nan, nan.clone());
+    assert!(!nan.approx_eq(&nan, 1.0));
+    
+    // Matching infinities are equal, opposite ones are not
+    let inf = Tensor::new(vec![2], vec![f32::INFINITY, f32::NEG_INFINITY]);
+    assert!(inf.approx_eq(&inf.clone(), 0.0));
+    assert!(!inf.approx_eq(&Tensor::new(vec![2], vec![f32::INFINITY, f32::INFINITY]), 1.0));
+}
+
+#[test]
+fn test_dtype_promotion() {
+    let half = Tensor::new(vec![2], vec![1.5, -2.0]).to_dtype(DataType::Float16);
+    let full = Tensor::new(vec![2], vec![0.25, 4.0]);
+    let ints = Tensor::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]).to_dtype(DataType::Int32);
+    
+    assert_eq!(half.add(&half).unwrap().dtype, DataType::Float16);
+    assert_eq!(full.add(&half).unwrap().dtype, DataType::Float32);
+    assert_eq!(half.multiply(&full).unwrap().dtype, DataType::Float32);
+    
+    // Unary ops keep the dtype; ops that produce fractions turn ints into floats
+    assert_eq!(half.relu().dtype, DataType::Float16);
+    assert_eq!(half.softmax().dtype, DataType::Float16);
+    assert_eq!(ints.transpose().dtype, DataType::Int32);
+    assert_eq!(ints.softmax().dtype, DataType::Float32);
+    let mut in_place = ints.clone();
+    in_place.gelu_();
+    assert_eq!(in_place.dtype, ints.gelu().dtype);
+    let mut scaled = ints.clone();
+    scaled.mul_scalar_(0.5);
+    assert_eq!(scaled.dtype, DataType::Float32);
+    let mut half_scaled = half.clone();
+    half_scaled.mul_scalar_(0.5);
+    assert_eq!(half_scaled.dtype, DataType::Float16);
+    
+    // Integer matmul accumulates in f32 and can be cast back
+    let product = ints.matmul(&ints).unwrap();
+    assert_eq!(product.dtype, DataType::Float32);
+    assert_eq!(product.to_dtype(DataType::Int32).data, vec![7.0, 10.0, 15.0, 22.0]);
+    
+    // Casting rounds to what the dtype can hold
+    let cast = Tensor::new(vec![3], vec![1.0 + 1e-4, 2.6, 1e6]);
+    assert_eq!(cast.to_dtype(DataType::Float16).data, vec![1.0, 2.599_609_4, f32::INFINITY]);
+    let edge = Tensor::new(vec![3], vec![65510.0, -65519.0, 65520.0]);
+    assert_eq!(edge.to_dtype(DataType::Float16).data, vec![65504.0, -65504.0, f32::INFINITY]);
+    assert_eq!(cast.to_dtype(DataType::Int64).data, vec![1.0, 3.0, 1e6]);
+}
+
+#[test]
+fn test_elementwise_ops_cover_remainder() {
+    // 19 elements: two full 8-lane chunks plus a scalar remainder when SIMD is on
+    let a = Tensor::new(vec![19], (0..19).map(|i| i as f32 - 9.5).collect());
+    let b = Tensor::new(vec![19], (0..19).map(|i| (i % 4) as f32 * 0.5).collect());
+    
+    let expected_relu: Vec<f32> = a.data.iter().map(|&x| x.max(0.0)).collect();
+    let expected_sum: Vec<f32> = a.data.iter().zip(&b.data).map(|(x, y)| x + y).collect();
+    let expected_product: Vec<f32> = a.data.iter().zip(&b.data).map(|(x, y)| x * y).collect();
+    
+    assert_eq!(a.relu().data, expected_relu);
+    assert_eq!(a.add(&b).unwrap().data, expected_sum);
+    assert_eq!(a.multiply(&b).unwrap().data, expected_product);
+    
+    let mut in_place = a.clone();
+    in_place.relu_();
+    assert_eq!(in_place.data, expected_relu);
+    let mut in_place = a.clone();
+    in_place.add_(&b).unwrap();
+    assert_eq!(in_place.data, expected_sum);
+    
+    // NaN clamps to zero on both paths, as f32::max does
+    assert_eq!(Tensor::new(vec![9], vec![f32::NAN; 9]).relu().data, vec![0.0; 9]);
+}
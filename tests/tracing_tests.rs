@@ -0,0 +1,81 @@
+use wingbeat::{ModelDecomposer, PromptProcessor, TornadoSwarm, WingbeatRng};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+/// Span name paired with the names of its fields
+type SpanRecord = (String, Vec<String>);
+
+/// Records span names with their field names, and event messages
+#[derive(Clone, Default)]
+struct CaptureLayer {
+    spans: Arc<Mutex<Vec<SpanRecord>>>,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+/// Extracts an event's message
+#[derive(Default)]
+struct MessageVisitor(Option<String>);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let fields = attrs.metadata().fields().iter().map(|f| f.name().to_string()).collect();
+        self.spans.lock().unwrap().push((attrs.metadata().name().to_string(), fields));
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        if let Some(message) = visitor.0 {
+            self.events.lock().unwrap().push(message);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_prompt_processing_emits_spans() {
+    let capture = CaptureLayer::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    
+    let swarm = Arc::new(TornadoSwarm::new());
+    let processor = PromptProcessor::with_rng(swarm, WingbeatRng::seed_from_u64(14));
+    processor.send_prompt("trace this prompt please").await.unwrap();
+    
+    let spans = capture.spans.lock().unwrap();
+    let has_span = |name: &str, field: &str| {
+        spans.iter().any(|(n, fields)| n == name && fields.iter().any(|f| f == field))
+    };
+    assert!(has_span("send_prompt", "prompt_id"));
+    assert!(has_span("distribute_fragments", "fragment_count"));
+    assert!(has_span("sweep_up", "tornado_id"));
+    
+    let events = capture.events.lock().unwrap();
+    assert!(events.iter().any(|e| e == "spawning tornado"));
+    assert!(events.iter().any(|e| e == "fragments swept up into the whirlwind"));
+}
+
+#[tokio::test]
+async fn test_reintegration_messages_are_intact_utf8() {
+    let capture = CaptureLayer::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+    
+    let decomposer = ModelDecomposer::new();
+    decomposer.reintegrate_results(HashMap::new()).await;
+    
+    let events = capture.events.lock().unwrap();
+    let text = events.join("\n");
+    assert!(events.iter().any(|e| e.starts_with('\u{1F504}'))); // 🔄
+    assert!(events.iter().any(|e| e.starts_with('\u{2705}'))); // ✅
+    assert!(!text.contains('\u{00F0}') && !text.contains('\u{00E2}'));
+}